@@ -19,6 +19,16 @@ where
     }
 }
 
+impl<Accu, Func, Res> AccuIter<Accu, Func, Res>
+where
+    Func: Fn(&Accu) -> (Accu, Option<Res>),
+{
+    /// The current accumulator.
+    pub fn accu(&self) -> &Accu {
+        &self.accu
+    }
+}
+
 #[allow(dead_code)]
 pub fn accu_iter<Accu, Func, Res>(initial: Accu, f: Func) -> AccuIter<Accu, Func, Res>
 where
@@ -0,0 +1,460 @@
+use std::ops::{Deref, DerefMut};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A fixed-capacity set of indices `0..capacity`, stored as one bit per index.
+///
+/// A cheap replacement for a `HashSet<usize>` when keeping track of visited
+/// nodes in dense searches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitSet {
+    words: Vec<u64>,
+    capacity: usize,
+    len: usize,
+}
+
+#[allow(dead_code)]
+impl BitSet {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            words: vec![0; capacity.div_ceil(WORD_BITS)],
+            capacity,
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of indices in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn position(&self, index: usize) -> (usize, u64) {
+        assert!(
+            index < self.capacity,
+            "Index {} out of range for BitSet of capacity {}",
+            index,
+            self.capacity
+        );
+        (index / WORD_BITS, 1 << (index % WORD_BITS))
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        let (word, mask) = self.position(index);
+        self.words[word] & mask != 0
+    }
+
+    /// Adds an index to the set.
+    ///
+    /// # Returns
+    ///
+    /// Whether the index was newly inserted.
+    pub fn insert(&mut self, index: usize) -> bool {
+        let (word, mask) = self.position(index);
+        let inserted = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+        self.len += usize::from(inserted);
+        inserted
+    }
+
+    /// Removes an index from the set.
+    ///
+    /// # Returns
+    ///
+    /// Whether the index was present.
+    pub fn remove(&mut self, index: usize) -> bool {
+        let (word, mask) = self.position(index);
+        let removed = self.words[word] & mask != 0;
+        self.words[word] &= !mask;
+        self.len -= usize::from(removed);
+        removed
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+        self.len = 0;
+    }
+
+    fn combine_with(&mut self, other: &BitSet, op: impl Fn(u64, u64) -> u64) {
+        assert_eq!(
+            self.capacity, other.capacity,
+            "Unable to combine BitSets of different capacities"
+        );
+        for (word, &other_word) in self.words.iter_mut().zip(&other.words) {
+            *word = op(*word, other_word);
+        }
+        self.len = self
+            .words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum();
+    }
+
+    pub fn union_with(&mut self, other: &BitSet) {
+        self.combine_with(other, |a, b| a | b);
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        self.combine_with(other, |a, b| a & b);
+    }
+
+    pub fn union(&self, other: &BitSet) -> BitSet {
+        let mut result = self.clone();
+        result.union_with(other);
+        result
+    }
+
+    pub fn intersection(&self, other: &BitSet) -> BitSet {
+        let mut result = self.clone();
+        result.intersect_with(other);
+        result
+    }
+
+    /// All indices of the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(word_index, &word)| {
+                (0..WORD_BITS)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| word_index * WORD_BITS + bit)
+            })
+    }
+}
+
+/// A [`BitSet`] indexed by `(row, column)` coordinates of a fixed-size grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridBitSet {
+    bits: BitSet,
+    dim: (usize, usize),
+}
+
+#[allow(dead_code)]
+impl GridBitSet {
+    pub fn new(dim: (usize, usize)) -> Self {
+        Self {
+            bits: BitSet::new(dim.0 * dim.1),
+            dim,
+        }
+    }
+
+    pub fn dim(&self) -> (usize, usize) {
+        self.dim
+    }
+
+    fn linear_index(&self, (row, col): (usize, usize)) -> usize {
+        assert!(
+            row < self.dim.0 && col < self.dim.1,
+            "Position {:?} out of range for GridBitSet of size {:?}",
+            (row, col),
+            self.dim
+        );
+        row * self.dim.1 + col
+    }
+
+    pub fn bits(&self) -> &BitSet {
+        &self.bits
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn contains(&self, pos: (usize, usize)) -> bool {
+        self.bits.contains(self.linear_index(pos))
+    }
+
+    pub fn insert(&mut self, pos: (usize, usize)) -> bool {
+        let index = self.linear_index(pos);
+        self.bits.insert(index)
+    }
+
+    pub fn remove(&mut self, pos: (usize, usize)) -> bool {
+        let index = self.linear_index(pos);
+        self.bits.remove(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    pub fn union_with(&mut self, other: &GridBitSet) {
+        assert_eq!(self.dim, other.dim);
+        self.bits.union_with(&other.bits);
+    }
+
+    pub fn intersect_with(&mut self, other: &GridBitSet) {
+        assert_eq!(self.dim, other.dim);
+        self.bits.intersect_with(&other.bits);
+    }
+
+    pub fn union(&self, other: &GridBitSet) -> GridBitSet {
+        let mut result = self.clone();
+        result.union_with(other);
+        result
+    }
+
+    pub fn intersection(&self, other: &GridBitSet) -> GridBitSet {
+        let mut result = self.clone();
+        result.intersect_with(other);
+        result
+    }
+
+    /// All positions of the set, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let cols = self.dim.1;
+        self.bits
+            .iter()
+            .map(move |index| (index / cols, index % cols))
+    }
+}
+
+/// A set of bits that supports temporary insertions, see [`VisitedSet::temporary_insert`].
+pub trait VisitedSet: Sized {
+    type Item: Copy;
+
+    fn insert_item(&mut self, item: Self::Item) -> bool;
+    fn remove_item(&mut self, item: Self::Item) -> bool;
+
+    /// Inserts an element into the set, returning a guard.
+    /// When the guard is destroyed, the element gets removed again.
+    ///
+    /// Behaves exactly like [`super::temporary_hashset::HashSetExt::temporary_insert`],
+    /// but without having to hash the element.
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating whether the insertion was successful (e.g. the item did not exist yet),
+    /// and the temporary set that includes the given item.
+    fn temporary_insert(&mut self, item: Self::Item) -> (bool, TemporaryBitSet<'_, Self>) {
+        let inserted = self.insert_item(item);
+        (
+            inserted,
+            TemporaryBitSet {
+                set: self,
+                item,
+                inserted,
+            },
+        )
+    }
+}
+
+impl VisitedSet for BitSet {
+    type Item = usize;
+
+    fn insert_item(&mut self, item: usize) -> bool {
+        self.insert(item)
+    }
+
+    fn remove_item(&mut self, item: usize) -> bool {
+        self.remove(item)
+    }
+}
+
+impl VisitedSet for GridBitSet {
+    type Item = (usize, usize);
+
+    fn insert_item(&mut self, item: (usize, usize)) -> bool {
+        self.insert(item)
+    }
+
+    fn remove_item(&mut self, item: (usize, usize)) -> bool {
+        self.remove(item)
+    }
+}
+
+pub struct TemporaryBitSet<'a, S: VisitedSet> {
+    set: &'a mut S,
+    item: S::Item,
+    inserted: bool,
+}
+
+impl<'a, S: VisitedSet> Deref for TemporaryBitSet<'a, S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        self.set
+    }
+}
+
+impl<'a, S: VisitedSet> DerefMut for TemporaryBitSet<'a, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.set
+    }
+}
+
+impl<'a, S: VisitedSet> Drop for TemporaryBitSet<'a, S> {
+    fn drop(&mut self) {
+        if self.inserted {
+            self.set.remove_item(self.item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn works() {
+        let mut set = BitSet::new(130);
+        set.insert(1);
+        set.insert(64);
+        set.insert(129);
+        assert_eq!(set.len(), 3);
+
+        {
+            let (insert_succeeded, temporary_set) = set.temporary_insert(4);
+
+            assert!(insert_succeeded);
+            assert_eq!(
+                temporary_set.iter().collect::<Vec<_>>(),
+                vec![1, 4, 64, 129]
+            );
+        }
+
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 64, 129]);
+
+        {
+            let (insert_succeeded, temporary_set) = set.temporary_insert(64);
+
+            assert!(!insert_succeeded);
+            assert_eq!(temporary_set.len(), 3);
+        }
+
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 64, 129]);
+    }
+
+    #[test]
+    fn set_operations() {
+        let mut a = GridBitSet::new((3, 3));
+        let mut b = GridBitSet::new((3, 3));
+        a.insert((0, 0));
+        a.insert((1, 1));
+        b.insert((1, 1));
+        b.insert((2, 2));
+
+        assert_eq!(
+            a.union(&b).iter().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 1), (2, 2)]
+        );
+        assert_eq!(a.intersection(&b).iter().collect::<Vec<_>>(), vec![(1, 1)]);
+        assert_eq!(a.union(&b).len(), 3);
+        assert_eq!(a.intersection(&b).len(), 1);
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with `cargo test --release -- --ignored --nocapture bitset`"]
+    fn benchmark_against_hashset() {
+        use crate::helpers::temporary_hashset::HashSetExt;
+        use std::{
+            collections::{HashSet, VecDeque},
+            hint::black_box,
+            time::Instant,
+        };
+
+        // A dense grid of 40000 cells, with a scattering of walls
+        const SIZE: usize = 200;
+        // The length of the walks started from every cell
+        const DEPTH: usize = 6;
+
+        fn neighbors((row, col): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+            [
+                (row.wrapping_sub(1), col),
+                (row + 1, col),
+                (row, col.wrapping_sub(1)),
+                (row, col + 1),
+            ]
+            .into_iter()
+            .filter(|&(row, col)| row < SIZE && col < SIZE && (row * 7 + col * 13) % 11 != 0)
+        }
+
+        // Floods the grid from the top left corner, counting the reached cells
+        fn bfs_hashset() -> usize {
+            let mut visited = HashSet::from([(0, 0)]);
+            let mut queue = VecDeque::from([(0, 0)]);
+            while let Some(pos) = queue.pop_front() {
+                queue.extend(neighbors(pos).filter(|&next| visited.insert(next)));
+            }
+            visited.len()
+        }
+
+        fn bfs_bitset() -> usize {
+            let mut visited = GridBitSet::new((SIZE, SIZE));
+            visited.insert((0, 0));
+            let mut queue = VecDeque::from([(0, 0)]);
+            while let Some(pos) = queue.pop_front() {
+                queue.extend(neighbors(pos).filter(|&next| visited.insert(next)));
+            }
+            visited.len()
+        }
+
+        // Counts the steps of all self-avoiding walks of up to `depth` steps
+        fn dfs_hashset(
+            visited: &mut HashSet<(usize, usize)>,
+            pos: (usize, usize),
+            depth: usize,
+        ) -> u64 {
+            let (inserted, mut visited) = visited.temporary_insert(pos);
+            if !inserted || depth == 0 {
+                return u64::from(inserted);
+            }
+            1 + neighbors(pos)
+                .map(|next| dfs_hashset(&mut visited, next, depth - 1))
+                .sum::<u64>()
+        }
+
+        fn dfs_bitset(visited: &mut GridBitSet, pos: (usize, usize), depth: usize) -> u64 {
+            let (inserted, mut visited) = visited.temporary_insert(pos);
+            if !inserted || depth == 0 {
+                return u64::from(inserted);
+            }
+            1 + neighbors(pos)
+                .map(|next| dfs_bitset(&mut visited, next, depth - 1))
+                .sum::<u64>()
+        }
+
+        let starts = || (0..SIZE).flat_map(|row| (0..SIZE).map(move |col| (row, col)));
+
+        let t0 = Instant::now();
+        let hashset_flooded = (0..100).map(|_| black_box(bfs_hashset())).max();
+        let t1 = Instant::now();
+        let bitset_flooded = (0..100).map(|_| black_box(bfs_bitset())).max();
+        let t2 = Instant::now();
+
+        let mut hashset = black_box(HashSet::new());
+        let hashset_steps = starts()
+            .map(|start| dfs_hashset(&mut hashset, start, DEPTH))
+            .sum::<u64>();
+        let t3 = Instant::now();
+        let mut bitset = black_box(GridBitSet::new((SIZE, SIZE)));
+        let bitset_steps = starts()
+            .map(|start| dfs_bitset(&mut bitset, start, DEPTH))
+            .sum::<u64>();
+        let t4 = Instant::now();
+
+        assert_eq!(hashset_flooded, bitset_flooded);
+        assert_eq!(hashset_steps, bitset_steps);
+        println!("{}x{} grid, 100 BFS floods:", SIZE, SIZE);
+        println!("  HashSet<(usize, usize)>: {} ms", (t1 - t0).as_millis());
+        println!("  GridBitSet:              {} ms", (t2 - t1).as_millis());
+        println!(
+            "{}x{} grid, walks of {} steps from every cell:",
+            SIZE, SIZE, DEPTH
+        );
+        println!("  HashSet<(usize, usize)>: {} ms", (t3 - t2).as_millis());
+        println!("  GridBitSet:              {} ms", (t4 - t3).as_millis());
+    }
+}
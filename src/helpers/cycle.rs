@@ -0,0 +1,274 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    ops::{Add, Sub},
+};
+
+/// A cycle in a sequence of states.
+///
+/// The state at step `start + length` is equal to the state at step `start`,
+/// and every later state repeats with a period of `length`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+#[allow(dead_code)]
+impl Cycle {
+    /// Maps an arbitrarily large step onto the equivalent step within the first
+    /// occurrence of the cycle.
+    pub fn reduce(&self, step: usize) -> usize {
+        if step < self.start {
+            step
+        } else {
+            self.start + (step - self.start) % self.length
+        }
+    }
+}
+
+/// Floyd's tortoise and hare cycle detection.
+///
+/// Only keeps two states in memory, but evaluates the state function
+/// more often than [`find_cycle_brent`].
+///
+/// Never returns if the sequence does not contain a cycle.
+///
+/// # Arguments
+///
+/// * `initial` - The state at step 0.
+/// * `f` - Computes the next state from the current one, like the function of
+///   [`super::accu_iter::accu_iter`].
+#[allow(dead_code)]
+pub fn find_cycle_floyd<S, F>(initial: S, f: F) -> Cycle
+where
+    S: PartialEq,
+    F: Fn(&S) -> S,
+{
+    let mut tortoise = f(&initial);
+    let mut hare = f(&tortoise);
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&f(&hare));
+    }
+
+    let mut start = 0;
+    let mut tortoise = initial;
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    let mut hare = f(&tortoise);
+    while tortoise != hare {
+        hare = f(&hare);
+        length += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Brent's cycle detection.
+///
+/// Only keeps two states in memory, and evaluates the state function
+/// less often than [`find_cycle_floyd`].
+///
+/// Never returns if the sequence does not contain a cycle.
+#[allow(dead_code)]
+pub fn find_cycle_brent<S, F>(initial: S, f: F) -> Cycle
+where
+    S: PartialEq + Clone,
+    F: Fn(&S) -> S,
+{
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = f(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = f(&hare);
+        length += 1;
+    }
+
+    let mut start = 0;
+    let mut hare = (0..length).fold(initial.clone(), |state, _| f(&state));
+    let mut tortoise = initial;
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Hash map based cycle detection, for sequences of states that can be summarized.
+///
+/// Remembers every state it has seen, but requires only a single pass over the sequence.
+/// This makes it the right choice for [`super::accu_iter::accu_iter`] or any other iterator
+/// that yields a hashable summary of the state at every step, starting with step 0.
+///
+/// # Returns
+///
+/// The first cycle, or `None` if the sequence ended before repeating itself.
+#[allow(dead_code)]
+pub fn find_cycle<K>(states: impl IntoIterator<Item = K>) -> Option<Cycle>
+where
+    K: Eq + Hash,
+{
+    let mut seen = HashMap::new();
+    for (step, state) in states.into_iter().enumerate() {
+        if let Some(start) = seen.insert(state, step) {
+            return Some(Cycle {
+                start,
+                length: step - start,
+            });
+        }
+    }
+    None
+}
+
+/// Computes the value of an accumulated metric at an arbitrarily large step,
+/// by extrapolating it across repetitions of the first cycle.
+///
+/// The metric has to grow by the same amount in every repetition of the cycle,
+/// like the height of a tower that a repeating pattern gets stacked on.
+///
+/// # Arguments
+///
+/// * `states` - Yields a hashable summary of the state together with the metric,
+///   for every step starting with step 0.
+/// * `step` - The step whose metric should be computed.
+///
+/// # Returns
+///
+/// The metric at `step`, or `None` if the sequence ended before reaching `step`
+/// or repeating itself.
+#[allow(dead_code)]
+pub fn value_at<K, M>(states: impl IntoIterator<Item = (K, M)>, step: usize) -> Option<M>
+where
+    K: Eq + Hash,
+    M: Copy + Add<Output = M> + Sub<Output = M>,
+{
+    let mut seen = HashMap::new();
+    let mut metrics = vec![];
+
+    for (current, (state, metric)) in states.into_iter().enumerate() {
+        metrics.push(metric);
+        if current == step {
+            return Some(metric);
+        }
+
+        if let Some(start) = seen.insert(state, current) {
+            let cycle = Cycle {
+                start,
+                length: current - start,
+            };
+            let growth = metrics[current] - metrics[start];
+            let repetitions = (step - start) / cycle.length;
+            return Some(metrics[cycle.reduce(step)] + repeated_sum(growth, repetitions));
+        }
+    }
+
+    None
+}
+
+/// Adds up `times` copies of `value`, for any `times` of at least 1.
+///
+/// Works by doubling, so unlike a multiplication `times` doesn't have to fit into `M`;
+/// only the result does.
+fn repeated_sum<M: Copy + Add<Output = M>>(value: M, mut times: usize) -> M {
+    let mut sum = None;
+    let mut power = value;
+    loop {
+        if times % 2 == 1 {
+            sum = Some(sum.map_or(power, |sum| sum + power));
+        }
+        times /= 2;
+        if times == 0 {
+            return sum.expect("times has to be at least 1");
+        }
+        power = power + power;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::accu_iter::accu_iter;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 2 -> ...
+    fn next(n: &u32) -> u32 {
+        if *n == 5 {
+            2
+        } else {
+            n + 1
+        }
+    }
+
+    const CYCLE: Cycle = Cycle {
+        start: 2,
+        length: 4,
+    };
+
+    #[test]
+    fn floyd() {
+        assert_eq!(find_cycle_floyd(0, next), CYCLE);
+    }
+
+    #[test]
+    fn brent() {
+        assert_eq!(find_cycle_brent(0, next), CYCLE);
+        assert_eq!(
+            find_cycle_brent(3, next),
+            Cycle {
+                start: 0,
+                length: 4
+            }
+        );
+    }
+
+    #[test]
+    fn hash_map() {
+        let states = accu_iter(0, |n| (next(n), Some(*n)));
+        assert_eq!(find_cycle(states), Some(CYCLE));
+        assert_eq!(find_cycle(0..10), None);
+    }
+
+    #[test]
+    fn extrapolation() {
+        // The state cycles, while the metric keeps growing.
+        let states = || {
+            accu_iter((0, 0u64), |&(n, sum)| {
+                ((next(&n), sum + u64::from(n)), Some((n, sum)))
+            })
+        };
+        let brute_force = |step| states().nth(step).unwrap().1;
+
+        for step in [0, 1, 2, 5, 6, 7, 20, 101] {
+            assert_eq!(value_at(states(), step), Some(brute_force(step)));
+        }
+
+        assert_eq!(
+            value_at(states(), 1_000_000_000_000),
+            Some(3_499_999_999_992)
+        );
+    }
+
+    #[test]
+    fn extrapolation_with_narrow_metric() {
+        // The metric doesn't grow, so it fits a u8 although the repetitions don't
+        let states = accu_iter(0, |n| (next(n), Some((*n, *n as u8))));
+        assert_eq!(value_at(states, 1_000_000_000_000), Some(4));
+
+        assert_eq!(repeated_sum(3i8, 42), 126);
+        assert_eq!(repeated_sum(-7i64, 1), -7);
+        assert_eq!(repeated_sum(0u8, usize::MAX), 0);
+    }
+}
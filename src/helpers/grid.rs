@@ -0,0 +1,519 @@
+use std::{
+    fmt,
+    num::TryFromIntError,
+    ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign},
+};
+
+use ndarray::Array2;
+
+use super::rendering::{Collector, RenderError, Renderable, ToColor};
+
+/// A position on a grid.
+///
+/// Coordinates are signed, so stepping off the edge of a grid always produces
+/// a valid point; bounds are only checked when the point is used to access a grid.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point {
+    pub row: isize,
+    pub col: isize,
+}
+
+#[allow(dead_code)]
+impl Point {
+    pub const ORIGIN: Point = Point { row: 0, col: 0 };
+
+    pub const fn new(row: isize, col: isize) -> Self {
+        Self { row, col }
+    }
+
+    pub fn step(self, direction: Direction) -> Self {
+        self + direction.offset()
+    }
+
+    pub fn step_n(self, direction: Direction, n: isize) -> Self {
+        self + direction.offset() * n
+    }
+
+    /// The four orthogonally adjacent points.
+    pub fn neighbors4(self) -> impl Iterator<Item = Point> {
+        Direction::ORTHOGONAL
+            .into_iter()
+            .map(move |dir| self.step(dir))
+    }
+
+    /// The eight orthogonally and diagonally adjacent points.
+    pub fn neighbors8(self) -> impl Iterator<Item = Point> {
+        Direction::ALL.into_iter().map(move |dir| self.step(dir))
+    }
+
+    pub fn manhattan_distance(self, other: Point) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+
+    pub fn chebyshev_distance(self, other: Point) -> usize {
+        std::cmp::max(self.row.abs_diff(other.row), self.col.abs_diff(other.col))
+    }
+
+    /// Converts the point to an index into an `Array2`, if both coordinates are positive.
+    pub fn to_index(self) -> Option<(usize, usize)> {
+        Some((self.row.try_into().ok()?, self.col.try_into().ok()?))
+    }
+}
+
+impl TryFrom<(usize, usize)> for Point {
+    type Error = TryFromIntError;
+
+    fn try_from((row, col): (usize, usize)) -> Result<Self, Self::Error> {
+        Ok(Self {
+            row: row.try_into()?,
+            col: col.try_into()?,
+        })
+    }
+}
+
+impl From<(isize, isize)> for Point {
+    fn from((row, col): (isize, isize)) -> Self {
+        Self { row, col }
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Point) -> Point {
+        Point::new(self.row + rhs.row, self.col + rhs.col)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, rhs: Point) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Point) -> Point {
+        Point::new(self.row - rhs.row, self.col - rhs.col)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, rhs: Point) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<isize> for Point {
+    type Output = Point;
+
+    fn mul(self, rhs: isize) -> Point {
+        Point::new(self.row * rhs, self.col * rhs)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.row, -self.col)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
+/// A direction on a grid, where `Up` points towards row 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+#[allow(dead_code)]
+impl Direction {
+    /// The directions of a 4-connected grid, clockwise.
+    pub const ORTHOGONAL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    /// The diagonal directions, clockwise.
+    pub const DIAGONAL: [Direction; 4] = [
+        Direction::UpRight,
+        Direction::DownRight,
+        Direction::DownLeft,
+        Direction::UpLeft,
+    ];
+
+    /// The directions of an 8-connected grid, clockwise.
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    pub fn offset(self) -> Point {
+        match self {
+            Direction::Up => Point::new(-1, 0),
+            Direction::UpRight => Point::new(-1, 1),
+            Direction::Right => Point::new(0, 1),
+            Direction::DownRight => Point::new(1, 1),
+            Direction::Down => Point::new(1, 0),
+            Direction::DownLeft => Point::new(1, -1),
+            Direction::Left => Point::new(0, -1),
+            Direction::UpLeft => Point::new(-1, -1),
+        }
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        Self::DIAGONAL.contains(&self)
+    }
+
+    /// Rotates clockwise in steps of 45 degrees.
+    fn rotate(self, eighths: usize) -> Self {
+        let index = Self::ALL.iter().position(|&dir| dir == self).unwrap();
+        Self::ALL[(index + eighths) % 8]
+    }
+
+    pub fn turn_right(self) -> Self {
+        self.rotate(2)
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.rotate(6)
+    }
+
+    pub fn turn_around(self) -> Self {
+        self.rotate(4)
+    }
+
+    pub fn turn_right_45(self) -> Self {
+        self.rotate(1)
+    }
+
+    pub fn turn_left_45(self) -> Self {
+        self.rotate(7)
+    }
+}
+
+/// Converts an index into an `Array2`, which never has more than `isize::MAX` elements.
+fn array_point(index: (usize, usize)) -> Point {
+    Point::try_from(index).expect("Array indices always fit into isize")
+}
+
+/// A dense, bounded grid with checked, point based access.
+///
+/// This is a thin wrapper around an `Array2`, which can be obtained again
+/// through [`Grid::array`] or [`Grid::into_array`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    data: Array2<T>,
+}
+
+#[allow(dead_code)]
+impl<T> Grid<T> {
+    pub fn new(data: Array2<T>) -> Self {
+        Self { data }
+    }
+
+    pub fn from_elem(rows: usize, cols: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(Array2::from_elem((rows, cols), value))
+    }
+
+    pub fn from_fn(rows: usize, cols: usize, mut f: impl FnMut(Point) -> T) -> Self {
+        Self::new(Array2::from_shape_fn((rows, cols), |index| {
+            f(array_point(index))
+        }))
+    }
+
+    pub fn rows(&self) -> usize {
+        self.data.nrows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.data.ncols()
+    }
+
+    pub fn array(&self) -> &Array2<T> {
+        &self.data
+    }
+
+    pub fn array_mut(&mut self) -> &mut Array2<T> {
+        &mut self.data
+    }
+
+    pub fn into_array(self) -> Array2<T> {
+        self.data
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.index_of(point).is_some()
+    }
+
+    fn index_of(&self, point: Point) -> Option<(usize, usize)> {
+        point
+            .to_index()
+            .filter(|&(row, col)| row < self.rows() && col < self.cols())
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index_of(point).map(|index| &self.data[index])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index_of(point).map(|index| &mut self.data[index])
+    }
+
+    /// All points of the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let cols = self.cols();
+        (0..self.rows()).flat_map(move |row| (0..cols).map(move |col| array_point((row, col))))
+    }
+
+    /// All cells of the grid together with their points, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.data
+            .indexed_iter()
+            .map(|(index, value)| (array_point(index), value))
+    }
+
+    /// The in-bounds orthogonal neighbors of a point.
+    pub fn neighbors4(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        point
+            .neighbors4()
+            .filter_map(|neighbor| self.get(neighbor).map(|value| (neighbor, value)))
+    }
+
+    /// The in-bounds orthogonal and diagonal neighbors of a point.
+    pub fn neighbors8(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        point
+            .neighbors8()
+            .filter_map(|neighbor| self.get(neighbor).map(|value| (neighbor, value)))
+    }
+
+    pub fn row(&self, row: usize) -> impl Iterator<Item = (Point, &T)> {
+        self.ray(
+            array_point((row, 0)).step(Direction::Left),
+            Direction::Right,
+        )
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = (Point, &T)> {
+        self.ray(array_point((0, col)).step(Direction::Up), Direction::Down)
+    }
+
+    /// Walks from `start` in the given direction until the edge of the grid is reached.
+    ///
+    /// The starting point itself is not part of the ray.
+    pub fn ray(&self, start: Point, direction: Direction) -> impl Iterator<Item = (Point, &T)> {
+        std::iter::successors(Some(start.step(direction)), move |&point| {
+            Some(point.step(direction))
+        })
+        .map_while(|point| self.get(point).map(|value| (point, value)))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.data.map(f))
+    }
+}
+
+#[allow(dead_code)]
+impl<T: ToColor> Grid<T> {
+    /// Adds the grid as a frame to a rendering collector.
+    pub fn add_frame(
+        &self,
+        collector: &mut impl Collector,
+        timestamp: f64,
+    ) -> Result<(), RenderError> {
+        collector.add_frame(&self.data, timestamp)
+    }
+}
+
+impl<T: ToColor> Renderable for Grid<T> {
+    fn add_to(&self, collector: &mut impl Collector, timestamp: f64) -> Result<(), RenderError> {
+        self.add_frame(collector, timestamp)
+    }
+}
+
+impl<T> From<Array2<T>> for Grid<T> {
+    fn from(data: Array2<T>) -> Self {
+        Self::new(data)
+    }
+}
+
+impl<T> From<Grid<T>> for Array2<T> {
+    fn from(grid: Grid<T>) -> Self {
+        grid.data
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point)
+            .unwrap_or_else(|| panic!("Point {} is outside of the grid", point))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("Point {} is outside of the grid", point))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.data.rows() {
+            for value in row {
+                write!(f, "{}", value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::{
+        input_parsing::{parse_as_2d_matrix, RaggedRows},
+        rendering::{
+            testing::{record, Gray},
+            Format, RecordingSettings, Renderers,
+        },
+    };
+
+    fn grid() -> Grid<u8> {
+        parse_as_2d_matrix::<u8>("123\n456\n789", RaggedRows::Strict)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Left.turn_around(), Direction::Right);
+        assert_eq!(Direction::UpLeft.turn_right_45(), Direction::Up);
+        assert_eq!(
+            Point::new(2, 3).step_n(Direction::DownLeft, 2),
+            Point::new(4, 1)
+        );
+    }
+
+    #[test]
+    fn checked_access() {
+        let grid = grid();
+
+        assert_eq!(grid.get(Point::new(1, 2)), Some(&6));
+        assert_eq!(grid.get(Point::new(-1, 0)), None);
+        assert_eq!(grid.get(Point::new(0, 3)), None);
+        assert_eq!(grid[Point::new(2, 0)], 7);
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = grid();
+
+        let values =
+            |it: &mut dyn Iterator<Item = (Point, &u8)>| it.map(|(_, &v)| v).collect::<Vec<_>>();
+
+        assert_eq!(values(&mut grid.neighbors4(Point::new(0, 0))), vec![2, 4]);
+        assert_eq!(
+            values(&mut grid.neighbors8(Point::new(0, 0))),
+            vec![2, 5, 4]
+        );
+        assert_eq!(
+            values(&mut grid.neighbors8(Point::new(1, 1))),
+            vec![2, 3, 6, 9, 8, 7, 4, 1]
+        );
+    }
+
+    #[test]
+    fn lines() {
+        let grid = grid();
+
+        let values =
+            |it: &mut dyn Iterator<Item = (Point, &u8)>| it.map(|(_, &v)| v).collect::<Vec<_>>();
+
+        assert_eq!(values(&mut grid.row(1)), vec![4, 5, 6]);
+        assert_eq!(values(&mut grid.column(2)), vec![3, 6, 9]);
+        assert_eq!(
+            values(&mut grid.ray(Point::new(2, 1), Direction::Up)),
+            vec![5, 2]
+        );
+        assert_eq!(
+            values(&mut grid.ray(Point::new(0, 0), Direction::Up)),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(grid().to_string(), "123\n456\n789\n");
+    }
+
+    #[test]
+    fn point_conversions() {
+        assert_eq!(Point::try_from((2usize, 3usize)), Ok(Point::new(2, 3)));
+        assert!(Point::try_from((usize::MAX, 0)).is_err());
+        assert_eq!(Point::new(2, 3).to_index(), Some((2, 3)));
+        assert_eq!(Point::new(-1, 3).to_index(), None);
+    }
+
+    #[test]
+    fn renders_frames() {
+        let grid = Grid::from_fn(2, 3, |point| Gray((point.row * 3 + point.col) as u8 * 10));
+        let settings = RecordingSettings {
+            format: Format::Apng,
+            scale: 2,
+            ..Default::default()
+        };
+        let (collector, writer) = Renderers::create_renderer(&settings).unwrap();
+        let path = std::env::temp_dir().join(format!("aoc_grid_{}.png", std::process::id()));
+
+        let (written, collected) = record(collector, writer, &path, Some(1), |collector| {
+            grid.add_to(collector, 0.0)
+        });
+        written.unwrap();
+        collected.unwrap();
+
+        let mut reader = png::Decoder::new(std::fs::File::open(&path).unwrap())
+            .read_info()
+            .unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut buffer).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(reader.info().size(), (6, 4));
+        // Every cell covers 2×2 pixels; the red channel is enough to tell them apart
+        let row = |y: usize| (0..6).map(|x| buffer[(y * 6 + x) * 4]).collect::<Vec<_>>();
+        assert_eq!(row(0), [0, 0, 10, 10, 20, 20]);
+        assert_eq!(row(1), row(0));
+        assert_eq!(row(3), [30, 30, 40, 40, 50, 50]);
+    }
+}
@@ -1,5 +1,5 @@
-use std::{error::Error, fmt};
-use std::{fmt::Debug, str::FromStr};
+use std::{collections::HashMap, error::Error, fmt};
+use std::{fmt::Debug, fmt::Display, str::FromStr};
 
 use ndarray::Array2;
 
@@ -14,52 +14,153 @@ impl fmt::Display for ParseError {
     }
 }
 
-fn get_2d_matrix_width_height(input_data: &str) -> (usize, usize) {
-    input_data
-        .trim()
+/// Determines how rows of different lengths are treated when parsing a grid.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum RaggedRows<T> {
+    /// Rows of different lengths are an error.
+    #[default]
+    Strict,
+    /// Short rows are filled up to the longest row with the given value.
+    Pad(T),
+    /// All rows are cut to the length of the shortest row.
+    Truncate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowLengths {
+    Exact,
+    Longest,
+    Shortest,
+}
+
+impl<T> RaggedRows<T> {
+    fn split(self) -> (RowLengths, Option<T>) {
+        match self {
+            RaggedRows::Strict => (RowLengths::Exact, None),
+            RaggedRows::Pad(value) => (RowLengths::Longest, Some(value)),
+            RaggedRows::Truncate => (RowLengths::Shortest, None),
+        }
+    }
+}
+
+/// Splits the input into its grid rows, together with their one-based line numbers
+/// in the original input.
+fn grid_rows(input_data: &str) -> impl Iterator<Item = (usize, &str)> {
+    let trimmed = input_data.trim();
+    let skipped_lines = input_data[..input_data.len() - input_data.trim_start().len()]
+        .matches('\n')
+        .count();
+
+    trimmed
         .lines()
-        .fold((usize::MAX, 0usize), |(width, height), row| {
-            (std::cmp::min(width, row.trim().len()), height + 1)
-        })
+        .map(str::trim_end)
+        .enumerate()
+        .map(move |(row, line)| (row + skipped_lines + 1, line))
+}
+
+/// A row whose length differs from the first one, with [`RaggedRows::Strict`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaggedRowError {
+    pub line: usize,
+    pub columns: usize,
+    pub expected: usize,
 }
 
-fn parse_as_2d_matrix_fn<T, F, G, E>(
+impl Error for RaggedRowError {}
+
+impl fmt::Display for RaggedRowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Line {} has {} column{}, expected {}",
+            self.line,
+            self.columns,
+            if self.columns == 1 { "" } else { "s" },
+            self.expected
+        )
+    }
+}
+
+/// Determines the width of a grid from the `(line, length)` of each of its rows.
+fn grid_width(
+    mut rows: impl Iterator<Item = (usize, usize)>,
+    row_lengths: RowLengths,
+) -> Result<usize, RaggedRowError> {
+    let Some((_, first)) = rows.next() else {
+        return Ok(0);
+    };
+    rows.try_fold(first, |width, (line, columns)| match row_lengths {
+        RowLengths::Exact if columns != width => Err(RaggedRowError {
+            line,
+            columns,
+            expected: width,
+        }),
+        RowLengths::Exact => Ok(width),
+        RowLengths::Longest => Ok(width.max(columns)),
+        RowLengths::Shortest => Ok(width.min(columns)),
+    })
+}
+
+fn parse_as_2d_matrix_fn<T, F, G, H, E>(
     input_data: &str,
     border_size: usize,
+    row_lengths: RowLengths,
     element: F,
     border: G,
-) -> Result<Array2<T>, E>
+    padding: H,
+) -> Result<Array2<T>, ParseError>
 where
     F: Fn(char) -> Result<T, E>,
     G: Fn() -> T,
+    H: Fn() -> T,
+    E: Display,
 {
-    let input_data = input_data.trim();
-
-    let (width, height) = get_2d_matrix_width_height(input_data);
+    let rows = grid_rows(input_data).collect::<Vec<_>>();
+    let width = grid_width(
+        rows.iter().map(|(line, row)| (*line, row.chars().count())),
+        row_lengths,
+    )
+    .map_err(|err| ParseError(err.to_string()))?;
+    let height = rows.len();
 
-    let mut parsed_data = input_data
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|ch| element(ch).map(|el| Some(el)))
-                .collect::<Result<Vec<_>, E>>()
+    // Cells that get truncated are skipped, so they don't have to be valid
+    let mut parsed_data = rows
+        .into_iter()
+        .map(|(line, row)| {
+            row.chars()
+                .take(width)
+                .enumerate()
+                .map(|(column, ch)| {
+                    element(ch).map(Some).map_err(|err| {
+                        ParseError(format!(
+                            "Invalid character {:?} in line {}, column {}: {}",
+                            ch,
+                            line,
+                            column + 1,
+                            err
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, ParseError>>()
+                .map(|elements| (line, elements))
         })
-        .collect::<Result<Vec<_>, E>>()?;
+        .collect::<Result<Vec<_>, ParseError>>()?;
 
     let matrix = Array2::from_shape_fn(
         (height + border_size * 2, width + border_size * 2),
         |(y, x)| {
-            if y < border_size || x < border_size {
-                border()
-            } else if let Some(elem) = parsed_data
-                .get_mut(y - border_size)
-                .and_then(|row| row.get_mut(x - border_size))
+            if y < border_size
+                || x < border_size
+                || y >= height + border_size
+                || x >= width + border_size
             {
+                border()
+            } else if let Some(elem) = parsed_data[y - border_size].1.get_mut(x - border_size) {
                 elem.take().unwrap_or_else(|| {
                     unreachable!("Every element of parsed_data should only be taken once!")
                 })
             } else {
-                border()
+                padding()
             }
         },
     );
@@ -67,39 +168,628 @@ where
     Ok(matrix)
 }
 
+/// Parses a grid of characters, surrounded by a border of `None` values.
+///
+/// How rows of different lengths are handled is determined by `ragged`;
+/// short rows can be padded with `None` just like the border.
 #[allow(dead_code)]
-pub fn parse_as_2d_matrix_with_border<T: FromStr>(
+pub fn parse_as_2d_matrix_with_border<T>(
     input_data: &str,
     border_size: usize,
-) -> Result<Array2<Option<T>>, T::Err> {
+    ragged: RaggedRows<Option<T>>,
+) -> Result<Array2<Option<T>>, ParseError>
+where
+    T: Clone + FromStr,
+    T::Err: Display,
+{
+    let (row_lengths, pad_value) = ragged.split();
+
     parse_as_2d_matrix_fn(
         input_data,
         border_size,
+        row_lengths,
         |c| format!("{}", c).parse::<T>().map(|e| Some(e)),
         || None,
+        || {
+            pad_value
+                .clone()
+                .unwrap_or_else(|| unreachable!("Only padded grids contain short rows"))
+        },
     )
 }
 
+/// Parses a grid of characters, surrounded by a border of `border_value`.
+///
+/// How rows of different lengths are handled is determined by `ragged`.
 #[allow(dead_code)]
-pub fn parse_as_2d_matrix_with_filled_border<T: Clone + FromStr>(
+pub fn parse_as_2d_matrix_with_filled_border<T>(
     input_data: &str,
     border_size: usize,
     border_value: T,
-) -> Result<Array2<T>, T::Err> {
+    ragged: RaggedRows<T>,
+) -> Result<Array2<T>, ParseError>
+where
+    T: Clone + FromStr,
+    T::Err: Display,
+{
+    let (row_lengths, pad_value) = ragged.split();
+
     parse_as_2d_matrix_fn(
         input_data,
         border_size,
+        row_lengths,
         |c| format!("{}", c).parse::<T>(),
         || border_value.clone(),
+        || {
+            pad_value
+                .clone()
+                .unwrap_or_else(|| unreachable!("Only padded grids contain short rows"))
+        },
     )
 }
 
+/// Parses a grid of characters.
+///
+/// How rows of different lengths are handled is determined by `ragged`.
 #[allow(dead_code)]
-pub fn parse_as_2d_matrix<T: FromStr>(input_data: &str) -> Result<Array2<T>, T::Err> {
+pub fn parse_as_2d_matrix<T>(
+    input_data: &str,
+    ragged: RaggedRows<T>,
+) -> Result<Array2<T>, ParseError>
+where
+    T: Clone + FromStr,
+    T::Err: Display,
+{
+    let (row_lengths, pad_value) = ragged.split();
+
     parse_as_2d_matrix_fn(
         input_data,
         0,
+        row_lengths,
         |c| format!("{}", c).parse::<T>(),
         || unreachable!(),
+        || {
+            pad_value
+                .clone()
+                .unwrap_or_else(|| unreachable!("Only padded grids contain short rows"))
+        },
     )
 }
+
+/// The `(row, column)` positions of every marker in a grid, in reading order.
+pub type Markers = HashMap<char, Vec<(usize, usize)>>;
+
+/// A character to look for with [`parse_as_2d_matrix_with_markers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Marker {
+    pub ch: char,
+    /// The grid has to contain the marker at least once.
+    pub required: bool,
+    /// The grid may contain the marker at most once.
+    pub unique: bool,
+}
+
+#[allow(dead_code)]
+impl Marker {
+    /// A marker that has to appear exactly once, like the start of a maze.
+    pub const fn new(ch: char) -> Self {
+        Self {
+            ch,
+            required: true,
+            unique: true,
+        }
+    }
+
+    /// Allows the marker to be missing.
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    /// Allows the marker to appear more than once.
+    pub fn repeated(mut self) -> Self {
+        self.unique = false;
+        self
+    }
+}
+
+/// Parses a grid of characters, surrounded by a border of `border_value`,
+/// and extracts the positions of the given markers.
+///
+/// Whether a marker may be missing or appear several times is up to its [`Marker`].
+/// Marker characters are still mapped through `cell`, so they can be given
+/// the value of the cell underneath them.
+///
+/// # Returns
+///
+/// The parsed grid, and the `(row, column)` coordinates of every marker within it,
+/// with the border already taken into account. Every marker has an entry,
+/// which is empty for optional markers that don't appear.
+#[allow(dead_code)]
+pub fn parse_as_2d_matrix_with_markers<T, F>(
+    input_data: &str,
+    border_size: usize,
+    border_value: T,
+    markers: &[Marker],
+    cell: F,
+) -> Result<(Array2<T>, Markers), ParseError>
+where
+    T: Clone,
+    F: Fn(char) -> Option<T>,
+{
+    let mut positions: Markers = markers.iter().map(|marker| (marker.ch, vec![])).collect();
+    // The one-based input position of the first occurrence of every marker, for error messages
+    let mut first_seen = HashMap::new();
+
+    for (row, (line, content)) in grid_rows(input_data).enumerate() {
+        for (column, ch) in content.chars().enumerate() {
+            let Some(marker) = markers.iter().find(|marker| marker.ch == ch) else {
+                continue;
+            };
+            if let (true, Some((first_line, first_column))) = (marker.unique, first_seen.get(&ch)) {
+                return Err(ParseError(format!(
+                    "Marker {:?} appears more than once: in line {}, column {} and in line {}, column {}",
+                    ch,
+                    first_line,
+                    first_column,
+                    line,
+                    column + 1
+                )));
+            }
+            first_seen.entry(ch).or_insert((line, column + 1));
+            positions
+                .entry(ch)
+                .or_default()
+                .push((row + border_size, column + border_size));
+        }
+    }
+
+    if let Some(missing) = markers
+        .iter()
+        .find(|marker| marker.required && positions[&marker.ch].is_empty())
+    {
+        return Err(ParseError(format!("Marker {:?} is missing", missing.ch)));
+    }
+
+    let matrix = parse_as_2d_matrix_fn(
+        input_data,
+        border_size,
+        RowLengths::Exact,
+        |c| cell(c).ok_or("unknown cell"),
+        || border_value.clone(),
+        || unreachable!(),
+    )?;
+
+    Ok((matrix, positions))
+}
+
+/// Error returned by the byte-level grid parsers when an input byte
+/// can not be mapped to a cell value.
+///
+/// Positions are one-based and refer to the original input, like those in [`ParseError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidByteError {
+    pub line: usize,
+    pub column: usize,
+    pub byte: u8,
+}
+
+impl Error for InvalidByteError {}
+
+impl fmt::Display for InvalidByteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid byte {:?} in line {}, column {}",
+            self.byte as char, self.line, self.column
+        )
+    }
+}
+
+/// Error returned by the byte-level grid parsers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ByteGridError {
+    InvalidByte(InvalidByteError),
+    RaggedRow(RaggedRowError),
+}
+
+impl Error for ByteGridError {}
+
+impl fmt::Display for ByteGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ByteGridError::InvalidByte(err) => write!(f, "{}", err),
+            ByteGridError::RaggedRow(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// A lookup table that maps every possible input byte to an optional cell value.
+///
+/// Bytes without an entry are reported as invalid by the byte-level grid parsers.
+#[derive(Debug, Clone)]
+pub struct ByteTable<T> {
+    entries: [Option<T>; 256],
+}
+
+#[allow(dead_code)]
+impl<T: Clone> ByteTable<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: std::array::from_fn(|_| None),
+        }
+    }
+
+    pub fn from_fn(mut f: impl FnMut(u8) -> Option<T>) -> Self {
+        Self {
+            entries: std::array::from_fn(|byte| f(byte as u8)),
+        }
+    }
+
+    pub fn with(mut self, byte: u8, value: T) -> Self {
+        self.entries[usize::from(byte)] = Some(value);
+        self
+    }
+
+    pub fn get(&self, byte: u8) -> Option<T> {
+        self.entries[usize::from(byte)].clone()
+    }
+}
+
+impl<T: Clone> Default for ByteTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl ByteTable<u8> {
+    /// Maps the ASCII digits `0`-`9` to their numerical value.
+    pub fn digits() -> Self {
+        Self::from_fn(|byte| byte.is_ascii_digit().then(|| byte - b'0'))
+    }
+}
+
+fn byte_lines(input_data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    input_data
+        .split(|&byte| byte == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+/// Parses a character grid byte by byte, writing every cell straight into
+/// the final matrix without any intermediate allocations.
+fn parse_bytes_as_2d_matrix_fn<T, F, G>(
+    input_data: &[u8],
+    border_size: usize,
+    ragged: RaggedRows<T>,
+    mut element: F,
+    mut border: G,
+) -> Result<Array2<T>, ByteGridError>
+where
+    T: Clone,
+    F: FnMut(u8) -> Option<T>,
+    G: FnMut() -> T,
+{
+    let skipped_lines = input_data[..input_data.len() - input_data.trim_ascii_start().len()]
+        .iter()
+        .filter(|&&byte| byte == b'\n')
+        .count();
+    let input_data = input_data.trim_ascii();
+
+    let lines = || byte_lines(input_data).map(<[u8]>::trim_ascii_end);
+    let (row_lengths, pad_value) = ragged.split();
+    let width = grid_width(
+        lines()
+            .enumerate()
+            .map(|(row, line)| (row + skipped_lines + 1, line.len())),
+        row_lengths,
+    )
+    .map_err(ByteGridError::RaggedRow)?;
+    let height = lines().count();
+    let shape = (height + border_size * 2, width + border_size * 2);
+
+    let mut cells = Vec::with_capacity(shape.0 * shape.1);
+
+    cells.extend((0..border_size * shape.1).map(|_| border()));
+    for (row, line) in lines().enumerate() {
+        cells.extend((0..border_size).map(|_| border()));
+        for (column, &byte) in line.iter().take(width).enumerate() {
+            cells.push(element(byte).ok_or_else(|| {
+                ByteGridError::InvalidByte(InvalidByteError {
+                    line: row + skipped_lines + 1,
+                    column: column + 1,
+                    byte,
+                })
+            })?);
+        }
+        if let Some(pad_value) = &pad_value {
+            cells.extend((line.len()..width).map(|_| pad_value.clone()));
+        }
+        cells.extend((0..border_size).map(|_| border()));
+    }
+    cells.extend((0..border_size * shape.1).map(|_| border()));
+
+    Ok(Array2::from_shape_vec(shape, cells).unwrap())
+}
+
+#[allow(dead_code)]
+pub fn parse_bytes_as_2d_matrix_with_border<T: Clone>(
+    input_data: &[u8],
+    border_size: usize,
+    ragged: RaggedRows<Option<T>>,
+    element: impl Fn(u8) -> Option<T>,
+) -> Result<Array2<Option<T>>, ByteGridError> {
+    parse_bytes_as_2d_matrix_fn(
+        input_data,
+        border_size,
+        ragged,
+        |byte| element(byte).map(Some),
+        || None,
+    )
+}
+
+#[allow(dead_code)]
+pub fn parse_bytes_as_2d_matrix_with_filled_border<T: Clone>(
+    input_data: &[u8],
+    border_size: usize,
+    border_value: T,
+    ragged: RaggedRows<T>,
+    element: impl Fn(u8) -> Option<T>,
+) -> Result<Array2<T>, ByteGridError> {
+    parse_bytes_as_2d_matrix_fn(input_data, border_size, ragged, element, || {
+        border_value.clone()
+    })
+}
+
+#[allow(dead_code)]
+pub fn parse_bytes_as_2d_matrix<T: Clone>(
+    input_data: &[u8],
+    ragged: RaggedRows<T>,
+    element: impl Fn(u8) -> Option<T>,
+) -> Result<Array2<T>, ByteGridError> {
+    parse_bytes_as_2d_matrix_fn(input_data, 0, ragged, element, || unreachable!())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn bytes_with_lookup_table() {
+        let table = ByteTable::digits();
+        let matrix =
+            parse_bytes_as_2d_matrix(b"123\r\n456\n", RaggedRows::Strict, |b| table.get(b))
+                .unwrap();
+
+        assert_eq!(matrix, array![[1, 2, 3], [4, 5, 6]]);
+    }
+
+    #[test]
+    fn bytes_with_border() {
+        let matrix =
+            parse_bytes_as_2d_matrix_with_border(b"#.\n.#", 1, RaggedRows::Strict, |b| match b {
+                b'#' => Some(true),
+                b'.' => Some(false),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(
+            matrix,
+            array![
+                [None, None, None, None],
+                [None, Some(true), Some(false), None],
+                [None, Some(false), Some(true), None],
+                [None, None, None, None],
+            ]
+        );
+    }
+
+    #[test]
+    fn bytes_with_filled_border() {
+        let table = ByteTable::digits();
+        let matrix =
+            parse_bytes_as_2d_matrix_with_filled_border(b"12\n34", 1, 0, RaggedRows::Strict, |b| {
+                table.get(b)
+            })
+            .unwrap();
+
+        assert_eq!(
+            matrix,
+            array![[0, 0, 0, 0], [0, 1, 2, 0], [0, 3, 4, 0], [0, 0, 0, 0]]
+        );
+    }
+
+    #[test]
+    fn bytes_invalid() {
+        let table = ByteTable::digits();
+        let err = parse_bytes_as_2d_matrix(b"\n123\n4x6", RaggedRows::Strict, |b| table.get(b))
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            ByteGridError::InvalidByte(InvalidByteError {
+                line: 3,
+                column: 2,
+                byte: b'x'
+            })
+        );
+        assert_eq!(err.to_string(), "Invalid byte 'x' in line 3, column 2");
+    }
+
+    #[test]
+    fn bytes_ragged() {
+        let table = ByteTable::digits();
+        let input = b"123\n45\n6789\n";
+
+        let err =
+            parse_bytes_as_2d_matrix(input, RaggedRows::default(), |b| table.get(b)).unwrap_err();
+        assert_eq!(err.to_string(), "Line 2 has 2 columns, expected 3");
+
+        let padded = parse_bytes_as_2d_matrix(input, RaggedRows::Pad(0), |b| table.get(b)).unwrap();
+        assert_eq!(padded, array![[1, 2, 3, 0], [4, 5, 0, 0], [6, 7, 8, 9]]);
+
+        let truncated =
+            parse_bytes_as_2d_matrix(input, RaggedRows::Truncate, |b| table.get(b)).unwrap();
+        assert_eq!(truncated, array![[1, 2], [4, 5], [6, 7]]);
+    }
+
+    #[test]
+    fn bytes_ragged_with_border() {
+        let table = ByteTable::digits();
+
+        let err =
+            parse_bytes_as_2d_matrix_with_border(b"12\n3", 1, RaggedRows::Strict, |b| table.get(b))
+                .unwrap_err();
+        assert!(matches!(err, ByteGridError::RaggedRow(_)), "{:?}", err);
+
+        let matrix =
+            parse_bytes_as_2d_matrix_with_border(b"12\n3", 1, RaggedRows::Pad(None), |b| {
+                table.get(b)
+            })
+            .unwrap();
+        assert_eq!(
+            matrix,
+            array![
+                [None, None, None, None],
+                [None, Some(1), Some(2), None],
+                [None, Some(3), None, None],
+                [None, None, None, None],
+            ]
+        );
+
+        let matrix =
+            parse_bytes_as_2d_matrix_with_filled_border(b"12\n3", 1, 9, RaggedRows::Pad(0), |b| {
+                table.get(b)
+            })
+            .unwrap();
+        assert_eq!(
+            matrix,
+            array![[9, 9, 9, 9], [9, 1, 2, 9], [9, 3, 0, 9], [9, 9, 9, 9]]
+        );
+    }
+
+    #[test]
+    fn ragged_with_border() {
+        let err = parse_as_2d_matrix_with_border::<u8>("12\n3", 1, RaggedRows::Strict).unwrap_err();
+        assert_eq!(err.0, "Line 2 has 1 column, expected 2");
+
+        let matrix =
+            parse_as_2d_matrix_with_border::<u8>("12\n3", 1, RaggedRows::Truncate).unwrap();
+        assert_eq!(
+            matrix,
+            array![
+                [None, None, None],
+                [None, Some(1), None],
+                [None, Some(3), None],
+                [None, None, None],
+            ]
+        );
+    }
+
+    #[test]
+    fn ragged_strict() {
+        let err = parse_as_2d_matrix::<u8>("\n123\n45\n678\n", RaggedRows::Strict).unwrap_err();
+
+        assert_eq!(err.0, "Line 3 has 2 columns, expected 3");
+    }
+
+    #[test]
+    fn ragged_pad() {
+        let matrix = parse_as_2d_matrix::<u8>("123\n45\n6789", RaggedRows::Pad(0)).unwrap();
+
+        assert_eq!(matrix, array![[1, 2, 3, 0], [4, 5, 0, 0], [6, 7, 8, 9]]);
+    }
+
+    #[test]
+    fn ragged_truncate() {
+        let matrix = parse_as_2d_matrix::<u8>("123\n45\n6789", RaggedRows::Truncate).unwrap();
+
+        assert_eq!(matrix, array![[1, 2], [4, 5], [6, 7]]);
+    }
+
+    #[test]
+    fn ragged_truncate_skips_cut_cells() {
+        let matrix = parse_as_2d_matrix::<u8>("12x\n34\n56y", RaggedRows::Truncate).unwrap();
+
+        assert_eq!(matrix, array![[1, 2], [3, 4], [5, 6]]);
+    }
+
+    #[test]
+    fn ragged_with_filled_border() {
+        let matrix =
+            parse_as_2d_matrix_with_filled_border::<u8>("12\n3", 1, 9, RaggedRows::Pad(0)).unwrap();
+
+        assert_eq!(
+            matrix,
+            array![[9, 9, 9, 9], [9, 1, 2, 9], [9, 3, 0, 9], [9, 9, 9, 9]]
+        );
+    }
+
+    #[test]
+    fn invalid_character() {
+        let err = parse_as_2d_matrix_with_filled_border::<u8>("12\n3x", 1, 0, RaggedRows::Strict)
+            .unwrap_err();
+
+        assert_eq!(
+            err.0,
+            "Invalid character 'x' in line 2, column 2: invalid digit found in string"
+        );
+    }
+
+    fn heights(ch: char) -> Option<u8> {
+        match ch {
+            'S' => Some(0),
+            'E' => Some(25),
+            'a'..='z' => Some(ch as u8 - b'a'),
+            _ => None,
+        }
+    }
+
+    const START_END: [Marker; 2] = [Marker::new('S'), Marker::new('E')];
+
+    #[test]
+    fn markers() {
+        let (matrix, markers) =
+            parse_as_2d_matrix_with_markers("Sab\nzEc", 1, u8::MAX, &START_END, heights).unwrap();
+
+        assert_eq!(matrix[(1, 1)], 0);
+        assert_eq!(matrix[(2, 2)], 25);
+        assert_eq!(matrix[(0, 0)], u8::MAX);
+        assert_eq!(
+            markers,
+            HashMap::from([('S', vec![(1, 1)]), ('E', vec![(2, 2)])])
+        );
+    }
+
+    #[test]
+    fn markers_optional_and_repeated() {
+        let markers = [
+            Marker::new('S'),
+            Marker::new('a').optional().repeated(),
+            Marker::new('x').optional(),
+        ];
+
+        let (_, positions) =
+            parse_as_2d_matrix_with_markers("Sab\nzac", 0, 0, &markers, heights).unwrap();
+
+        assert_eq!(positions[&'S'], [(0, 0)]);
+        assert_eq!(positions[&'a'], [(0, 1), (1, 1)]);
+        assert!(positions[&'x'].is_empty());
+    }
+
+    #[test]
+    fn markers_missing_or_duplicate() {
+        let err =
+            parse_as_2d_matrix_with_markers("Sab\nzac", 0, 0, &START_END, heights).unwrap_err();
+        assert_eq!(err.0, "Marker 'E' is missing");
+
+        let err =
+            parse_as_2d_matrix_with_markers("\nSaE\nzSc", 1, 0, &START_END, heights).unwrap_err();
+        assert_eq!(
+            err.0,
+            "Marker 'S' appears more than once: in line 2, column 1 and in line 3, column 2"
+        );
+    }
+}
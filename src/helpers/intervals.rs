@@ -0,0 +1,256 @@
+use std::{fmt, ops::RangeInclusive};
+
+/// An integer type that intervals can be built from.
+pub trait Discrete: Copy + Ord {
+    fn checked_succ(self) -> Option<Self>;
+    fn checked_pred(self) -> Option<Self>;
+    /// The number of values in `start..=end`, saturating at `u64::MAX`.
+    fn count(start: Self, end: Self) -> u64;
+}
+
+macro_rules! impl_discrete {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl Discrete for $t {
+                fn checked_succ(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+                fn checked_pred(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+                fn count(start: Self, end: Self) -> u64 {
+                    u64::try_from(end as i128 - start as i128 + 1).unwrap_or(u64::MAX)
+                }
+            }
+        )*
+    };
+}
+
+impl_discrete!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// A set of integers, stored as a sorted list of disjoint, non-adjacent, inclusive intervals.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<(T, T)>,
+}
+
+impl<T: Discrete> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Discrete> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { intervals: vec![] }
+    }
+
+    /// Sorts the intervals and merges the ones that overlap or touch.
+    fn normalized(mut intervals: Vec<(T, T)>) -> Self {
+        intervals.retain(|(start, end)| start <= end);
+        intervals.sort_unstable();
+
+        let mut merged: Vec<(T, T)> = Vec::with_capacity(intervals.len());
+        for (start, end) in intervals {
+            match merged.last_mut() {
+                Some(last) if last.1.checked_succ().is_none_or(|next| start <= next) => {
+                    last.1 = last.1.max(end);
+                }
+                _ => merged.push((start, end)),
+            }
+        }
+
+        Self { intervals: merged }
+    }
+
+    /// All intervals of the set, in ascending order.
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.intervals.iter().map(|&(start, end)| start..=end)
+    }
+
+    /// The number of disjoint intervals.
+    pub fn interval_count(&self) -> usize {
+        self.intervals.len()
+    }
+
+    /// The total number of values in the set, saturating at `u64::MAX`.
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().fold(0, |len, &(start, end)| {
+            len.saturating_add(T::count(start, end))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The smallest and the largest value of the set.
+    pub fn hull(&self) -> Option<RangeInclusive<T>> {
+        Some(self.intervals.first()?.0..=self.intervals.last()?.1)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let index = self.intervals.partition_point(|&(_, end)| end < value);
+        self.intervals
+            .get(index)
+            .is_some_and(|&(start, _)| start <= value)
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        *self = self.union(&range.into());
+    }
+
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        *self = self.difference(&range.into());
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::normalized(
+            self.intervals
+                .iter()
+                .chain(other.intervals.iter())
+                .copied()
+                .collect(),
+        )
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = vec![];
+        let (mut a, mut b) = (
+            self.intervals.iter().peekable(),
+            other.intervals.iter().peekable(),
+        );
+
+        while let (Some(&&(a_start, a_end)), Some(&&(b_start, b_end))) = (a.peek(), b.peek()) {
+            let (start, end) = (a_start.max(b_start), a_end.min(b_end));
+            if start <= end {
+                result.push((start, end));
+            }
+            if a_end < b_end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+
+        Self { intervals: result }
+    }
+
+    /// All values within `bounds` that are not part of the set.
+    pub fn complement(&self, bounds: RangeInclusive<T>) -> Self {
+        let (lower, upper) = bounds.into_inner();
+        let mut result = vec![];
+        let mut next_start = Some(lower);
+
+        for &(start, end) in &self.intervals {
+            let Some(gap_start) = next_start else {
+                break;
+            };
+            if let Some(gap_end) = start.checked_pred() {
+                result.push((gap_start, gap_end.min(upper)));
+            }
+            next_start = end.checked_succ().map(|next| next.max(lower));
+        }
+        if let Some(gap_start) = next_start {
+            result.push((gap_start, upper));
+        }
+
+        Self::normalized(result)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        match self.hull() {
+            Some(hull) => self.intersection(&other.complement(hull)),
+            None => Self::new(),
+        }
+    }
+
+    /// Whether the two sets have at least one value in common.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// Whether every value of `other` is also part of this set.
+    pub fn contains_set(&self, other: &Self) -> bool {
+        self.intersection(other) == *other
+    }
+}
+
+impl<T: Discrete> From<RangeInclusive<T>> for IntervalSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        Self::normalized(vec![range.into_inner()])
+    }
+}
+
+impl<T: Discrete> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        Self::normalized(iter.into_iter().map(RangeInclusive::into_inner).collect())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.intervals.iter().map(|(start, end)| start..=end))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[RangeInclusive<i32>]) -> IntervalSet<i32> {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn normalizes() {
+        let s = set(&[5..=7, 1..=2, 3..=3, 10..=12, 11..=15]);
+
+        assert_eq!(s, set(&[1..=3, 5..=7, 10..=15]));
+        assert_eq!(s.len(), 12);
+        assert!(s.contains(6));
+        assert!(!s.contains(4));
+        assert!(!s.contains(16));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = set(&[1..=5, 10..=20]);
+        let b = set(&[4..=12, 18..=30]);
+
+        assert_eq!(a.union(&b), set(&[1..=30]));
+        assert_eq!(a.intersection(&b), set(&[4..=5, 10..=12, 18..=20]));
+        assert_eq!(a.difference(&b), set(&[1..=3, 13..=17]));
+        assert_eq!(a.complement(0..=25), set(&[0..=0, 6..=9, 21..=25]));
+        assert_eq!(a.complement(3..=12), set(&[6..=9]));
+        assert!(a.overlaps(&b));
+        assert!(!a.contains_set(&b));
+        assert!(a.contains_set(&set(&[2..=3, 15..=15])));
+    }
+
+    #[test]
+    fn extremes() {
+        let mut s: IntervalSet<u8> = (250..=255).into();
+        s.insert(0..=3);
+
+        assert_eq!(s.len(), 10);
+        assert_eq!(s.complement(0..=255), (4..=249).into());
+
+        s.remove(2..=251);
+        assert_eq!(s.ranges().collect::<Vec<_>>(), vec![0..=1, 252..=255]);
+    }
+
+    #[test]
+    fn full_range() {
+        let s: IntervalSet<u64> = (0..=u64::MAX).into();
+        assert_eq!(s.len(), u64::MAX);
+        assert_eq!(s.complement(0..=u64::MAX), IntervalSet::new());
+
+        let s: IntervalSet<i64> = (i64::MIN..=i64::MAX).into();
+        assert_eq!(s.len(), u64::MAX);
+        assert_eq!(IntervalSet::from(i64::MIN..=-1).len(), 1 << 63);
+    }
+}
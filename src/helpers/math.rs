@@ -0,0 +1,428 @@
+use std::{
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+/// The greatest common divisor of two numbers; `gcd(0, 0)` is `0`.
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The least common multiple of two numbers, or `None` if it does not fit into a `u64`.
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// The greatest common divisor of all numbers; `0` for no numbers.
+#[allow(dead_code)]
+pub fn gcd_all(values: impl IntoIterator<Item = u64>) -> u64 {
+    values.into_iter().fold(0, gcd)
+}
+
+/// The least common multiple of all numbers; `1` for no numbers.
+///
+/// Returns `None` if the result does not fit into a `u64`.
+#[allow(dead_code)]
+pub fn lcm_all(values: impl IntoIterator<Item = u64>) -> Option<u64> {
+    values.into_iter().try_fold(1, lcm)
+}
+
+/// The extended euclidean algorithm.
+///
+/// # Returns
+///
+/// `(g, x, y)` with `g = gcd(a, b) = a*x + b*y`, where `g` is never negative.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The multiplicative inverse of `a` modulo `modulus`,
+/// or `None` if `a` and `modulus` are not coprime.
+pub fn mod_inverse(a: u64, modulus: u64) -> Option<u64> {
+    if modulus == 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(i128::from(a), i128::from(modulus));
+    (g == 1).then(|| x.rem_euclid(i128::from(modulus)) as u64)
+}
+
+/// Solves a system of congruences `x ≡ residue (mod modulus)` with the chinese remainder theorem.
+///
+/// The moduli do not need to be coprime.
+///
+/// # Returns
+///
+/// The smallest non-negative solution together with the lcm of all moduli,
+/// the period in which the solution repeats.
+/// `None` if the system has no solution, or if the lcm of the moduli does not fit into a `u64`.
+#[allow(dead_code)]
+pub fn crt(congruences: impl IntoIterator<Item = (u64, u64)>) -> Option<(u64, u64)> {
+    congruences
+        .into_iter()
+        .try_fold((0u64, 1u64), |(x, period), (residue, modulus)| {
+            if modulus == 0 {
+                return None;
+            }
+            let (x, period, residue, modulus) = (
+                i128::from(x),
+                i128::from(period),
+                i128::from(residue % modulus),
+                i128::from(modulus),
+            );
+
+            // Find k with x + k * period ≡ residue (mod modulus)
+            let (g, inverse, _) = extended_gcd(period, modulus);
+            let difference = residue - x;
+            if difference % g != 0 {
+                return None;
+            }
+            let step = modulus / g;
+            let k = ((difference / g).rem_euclid(step) as u128 * inverse.rem_euclid(step) as u128
+                % step as u128) as i128;
+
+            let new_period = u64::try_from(period.checked_mul(step)?).ok()?;
+            let new_x = (x + k * period).rem_euclid(i128::from(new_period));
+            Some((new_x as u64, new_period))
+        })
+}
+
+/// An integer modulo `modulus`.
+///
+/// All operations keep the value within `0..modulus`; combining two values
+/// with different moduli is an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModInt {
+    value: u64,
+    modulus: u64,
+}
+
+#[allow(dead_code)]
+impl ModInt {
+    pub fn new(value: u64, modulus: u64) -> Self {
+        assert!(modulus > 0, "ModInt modulus must not be zero");
+        Self {
+            value: value % modulus,
+            modulus,
+        }
+    }
+
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    pub fn modulus(self) -> u64 {
+        self.modulus
+    }
+
+    fn with_value(self, value: u128) -> Self {
+        Self {
+            value: (value % u128::from(self.modulus)) as u64,
+            modulus: self.modulus,
+        }
+    }
+
+    fn same_modulus(self, other: Self) -> Option<Self> {
+        (self.modulus == other.modulus).then_some(other)
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let other = self.same_modulus(other)?;
+        Some(self.with_value(u128::from(self.value) + u128::from(other.value)))
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let other = self.same_modulus(other)?;
+        Some(self.with_value(
+            u128::from(self.value) + u128::from(self.modulus) - u128::from(other.value),
+        ))
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let other = self.same_modulus(other)?;
+        Some(self.with_value(u128::from(self.value) * u128::from(other.value)))
+    }
+
+    /// The multiplicative inverse, if the value and the modulus are coprime.
+    pub fn inverse(self) -> Option<Self> {
+        mod_inverse(self.value, self.modulus).map(|value| Self::new(value, self.modulus))
+    }
+
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        self.checked_mul(self.same_modulus(other)?.inverse()?)
+    }
+
+    pub fn pow(self, mut exponent: u64) -> Self {
+        let mut result = self.with_value(1);
+        let mut base = self;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exponent >>= 1;
+        }
+        result
+    }
+}
+
+impl Add for ModInt {
+    type Output = ModInt;
+
+    fn add(self, rhs: ModInt) -> ModInt {
+        self.checked_add(rhs)
+            .unwrap_or_else(|| panic!("Moduli differ: {} and {}", self.modulus, rhs.modulus))
+    }
+}
+
+impl Add<u64> for ModInt {
+    type Output = ModInt;
+
+    fn add(self, rhs: u64) -> ModInt {
+        self + ModInt::new(rhs, self.modulus)
+    }
+}
+
+impl Sub for ModInt {
+    type Output = ModInt;
+
+    fn sub(self, rhs: ModInt) -> ModInt {
+        self.checked_sub(rhs)
+            .unwrap_or_else(|| panic!("Moduli differ: {} and {}", self.modulus, rhs.modulus))
+    }
+}
+
+impl Mul for ModInt {
+    type Output = ModInt;
+
+    fn mul(self, rhs: ModInt) -> ModInt {
+        self.checked_mul(rhs)
+            .unwrap_or_else(|| panic!("Moduli differ: {} and {}", self.modulus, rhs.modulus))
+    }
+}
+
+impl Mul<u64> for ModInt {
+    type Output = ModInt;
+
+    fn mul(self, rhs: u64) -> ModInt {
+        self * ModInt::new(rhs, self.modulus)
+    }
+}
+
+impl Neg for ModInt {
+    type Output = ModInt;
+
+    fn neg(self) -> ModInt {
+        self.with_value(0) - self
+    }
+}
+
+impl fmt::Display for ModInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (mod {})", self.value, self.modulus)
+    }
+}
+
+/// An integer whose arithmetic panics on overflow, even in release builds.
+///
+/// Useful for puzzle answers that might exceed their integer type without anyone noticing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Checked<T>(pub T);
+
+macro_rules! impl_checked {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl Add for Checked<$t> {
+                type Output = Self;
+                fn add(self, rhs: Self) -> Self {
+                    Checked(self.0.checked_add(rhs.0).unwrap_or_else(|| {
+                        panic!("Overflow: {} + {}", self.0, rhs.0)
+                    }))
+                }
+            }
+            impl Sub for Checked<$t> {
+                type Output = Self;
+                fn sub(self, rhs: Self) -> Self {
+                    Checked(self.0.checked_sub(rhs.0).unwrap_or_else(|| {
+                        panic!("Overflow: {} - {}", self.0, rhs.0)
+                    }))
+                }
+            }
+            impl Mul for Checked<$t> {
+                type Output = Self;
+                fn mul(self, rhs: Self) -> Self {
+                    Checked(self.0.checked_mul(rhs.0).unwrap_or_else(|| {
+                        panic!("Overflow: {} * {}", self.0, rhs.0)
+                    }))
+                }
+            }
+            impl std::iter::Sum for Checked<$t> {
+                fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                    iter.fold(Checked(0), |a, b| a + b)
+                }
+            }
+            impl std::iter::Product for Checked<$t> {
+                fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+                    iter.fold(Checked(1), |a, b| a * b)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked!(u32, u64, u128, usize, i32, i64, i128, isize);
+
+impl<T: fmt::Display> fmt::Display for Checked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small deterministic pseudo random number generator (xorshift),
+    /// so that the property tests are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self, max: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % max
+        }
+    }
+
+    const ITERATIONS: usize = 10_000;
+
+    #[test]
+    fn gcd_lcm_properties() {
+        let mut rng = Rng(0x2022);
+        for _ in 0..ITERATIONS {
+            let (a, b) = (rng.next(1_000_000) + 1, rng.next(1_000_000) + 1);
+            let g = gcd(a, b);
+            let l = lcm(a, b).unwrap();
+
+            assert_eq!(a % g, 0);
+            assert_eq!(b % g, 0);
+            assert_eq!(gcd(a / g, b / g), 1);
+            assert_eq!(g * l, a * b);
+        }
+
+        assert_eq!(gcd_all([12, 18, 30]), 6);
+        assert_eq!(lcm_all([2, 3, 5, 7, 11, 13, 17, 19, 23]), Some(223092870));
+        assert_eq!(lcm_all([u64::MAX, u64::MAX - 1]), None);
+    }
+
+    #[test]
+    fn inverse_properties() {
+        let mut rng = Rng(2022);
+        for _ in 0..ITERATIONS {
+            let modulus = rng.next(1_000_000_007) + 2;
+            let a = rng.next(modulus);
+            match mod_inverse(a, modulus) {
+                Some(inverse) => {
+                    assert_eq!((ModInt::new(a, modulus) * inverse).value(), 1);
+                }
+                None => assert_ne!(gcd(a, modulus), 1),
+            }
+        }
+    }
+
+    #[test]
+    fn modint_properties() {
+        let mut rng = Rng(7);
+        for _ in 0..ITERATIONS {
+            let modulus = rng.next(u64::MAX - 1) + 1;
+            let (a, b, c) = (
+                ModInt::new(rng.next(u64::MAX), modulus),
+                ModInt::new(rng.next(u64::MAX), modulus),
+                ModInt::new(rng.next(u64::MAX), modulus),
+            );
+
+            assert_eq!((a + b) - b, a);
+            assert_eq!(a + (-a), ModInt::new(0, modulus));
+            assert_eq!(a * (b + c), a * b + a * c);
+            assert_eq!(a.pow(3), a * a * a);
+        }
+
+        assert_eq!(ModInt::new(3, 7).pow(6).value(), 1);
+        assert_eq!(ModInt::new(3, 7).checked_add(ModInt::new(3, 8)), None);
+        assert_eq!(
+            ModInt::new(6, 7)
+                .checked_div(ModInt::new(3, 7))
+                .unwrap()
+                .value(),
+            2
+        );
+    }
+
+    #[test]
+    fn crt_properties() {
+        let mut rng = Rng(99);
+        for _ in 0..ITERATIONS {
+            let congruences = (0..rng.next(4) + 1)
+                .map(|_| {
+                    let modulus = rng.next(30) + 1;
+                    (rng.next(modulus), modulus)
+                })
+                .collect::<Vec<_>>();
+
+            match crt(congruences.iter().copied()) {
+                Some((x, period)) => {
+                    assert_eq!(period, lcm_all(congruences.iter().map(|c| c.1)).unwrap());
+                    assert!(x < period);
+                    for &(residue, modulus) in &congruences {
+                        assert_eq!(x % modulus, residue);
+                    }
+                }
+                None => {
+                    // Verify by brute force that there really is no solution
+                    let period = lcm_all(congruences.iter().map(|c| c.1)).unwrap();
+                    assert!((0..period).all(|x| congruences.iter().any(|&(r, m)| x % m != r)));
+                }
+            }
+        }
+
+        // Non-coprime moduli
+        assert_eq!(crt([(2, 6), (8, 10)]), Some((8, 30)));
+        assert_eq!(crt([(1, 6), (2, 10)]), None);
+    }
+
+    #[test]
+    fn checked() {
+        assert_eq!(Checked(3u64) * Checked(4) + Checked(1), Checked(13));
+        assert_eq!(
+            [1u32, 2, 3].map(Checked).into_iter().sum::<Checked<u32>>(),
+            Checked(6)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow: 4294967295 + 1")]
+    fn checked_overflow() {
+        let _ = Checked(u32::MAX) + Checked(1);
+    }
+}
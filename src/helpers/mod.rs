@@ -1,9 +1,20 @@
 pub mod accu_iter;
+pub mod bitset;
+pub mod cycle;
+pub mod grid;
 pub mod image_manipulation;
 pub mod input_parsing;
+pub mod intervals;
+pub mod math;
 pub mod nom;
+pub mod ocr;
 pub mod rendering;
+pub mod search;
+pub mod simulation;
+pub mod sparse_grid;
 pub mod temporary_hashset;
+pub mod undo_log;
+pub mod vm;
 
 #[macro_export]
 macro_rules! aoc_tests {
@@ -95,20 +106,31 @@ macro_rules! renderers {
             )*
         }
 
-        fn run_renderer(day: u8, task: u8, _data: &str) -> Result<Vec<String>> {
+        fn run_renderer(
+            day: u8,
+            task: u8,
+            _data: &str,
+            _options: &$crate::helpers::rendering::RenderOptions,
+        ) -> Result<Vec<$crate::helpers::rendering::Artifact>> {
             let day_str = format!("day{:0>2}", day);
             let task_str = format!("task{}", task);
 
             match (day_str.as_str(), task_str.as_str()) {
                 $($(
                     (stringify!($day), stringify!($task)) => {
-                        println!(
+                        let message = format!(
                             "Rendering {}::{} ...",
                             stringify!($day),
                             stringify!($task)
                         );
+                        // Keep the rendering clean if it gets piped into another program
+                        if _options.writes_to_stdout() {
+                            eprintln!("{}", message);
+                        } else {
+                            println!("{}", message);
+                        }
                         let input_data = solutions::$day::parse_input(_data);
-                        Ok(renderers::$day::$task(&input_data))
+                        Ok(renderers::$day::$task(&input_data, _options)?)
                     },
                 )*)*
                 _ => Err(anyhow!(
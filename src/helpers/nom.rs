@@ -66,9 +66,10 @@ pub use nom::{
     branch::alt,
     bytes::complete::tag,
     character::complete::{
-        anychar, char, line_ending, not_line_ending, one_of, space0, space1, u16, u32, u64,
+        alpha1, anychar, char, i64, line_ending, not_line_ending, one_of, satisfy, space0, space1,
+        u16, u32, u64,
     },
     combinator::map,
-    multi::{count, many1, many1_count, separated_list1},
+    multi::{count, many1, many1_count, separated_list0, separated_list1},
     sequence::{delimited, pair, preceded, separated_pair, tuple},
 };
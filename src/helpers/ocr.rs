@@ -0,0 +1,108 @@
+use ndarray::{s, Array2};
+
+/// The height of a letter of the puzzle font, in pixels.
+pub const LETTER_HEIGHT: usize = 6;
+/// The width of a letter of the puzzle font, in pixels.
+pub const LETTER_WIDTH: usize = 4;
+/// The distance between the left edges of two neighboring letters.
+const LETTER_STRIDE: usize = LETTER_WIDTH + 1;
+
+/// The letters of the 4x6 font used by the puzzles, as far as they are known.
+const FONT: [(char, [&str; LETTER_HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+fn recognize_letter(glyph: &[String]) -> char {
+    FONT.iter()
+        .find(|(_, rows)| rows.iter().zip(glyph).all(|(a, b)| a == b))
+        .map_or('?', |&(letter, _)| letter)
+}
+
+/// Reads the letters written on a screen with the puzzle font.
+///
+/// # Arguments
+///
+/// * `screen` - The pixels of the screen, `true` meaning lit. Has to be exactly
+///   one letter high; the letters are expected to start at the left edge,
+///   separated by one column of unlit pixels.
+///
+/// # Returns
+///
+/// The recognized text; unknown letters are replaced with `?`.
+pub fn read_letters(screen: &Array2<bool>) -> String {
+    assert_eq!(
+        screen.nrows(),
+        LETTER_HEIGHT,
+        "The screen has to be exactly one letter high"
+    );
+
+    (0..screen.ncols().div_ceil(LETTER_STRIDE))
+        .map(|letter| {
+            let start = letter * LETTER_STRIDE;
+            let end = (start + LETTER_WIDTH).min(screen.ncols());
+            let glyph = screen
+                .slice(s![.., start..end])
+                .rows()
+                .into_iter()
+                .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect())
+                .collect::<Vec<String>>();
+            recognize_letter(&glyph)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(rows: &[&str]) -> Array2<bool> {
+        let cols = rows[0].len();
+        Array2::from_shape_fn((rows.len(), cols), |(row, col)| {
+            rows[row].as_bytes()[col] == b'#'
+        })
+    }
+
+    #[test]
+    fn reads_letters() {
+        let screen = screen(&[
+            "#..#.####.###..###..",
+            "#..#.#....#..#.#..#.",
+            "####.###..#..#.#..#.",
+            "#..#.#....###..###..",
+            "#..#.#....#.#..#....",
+            "#..#.####.#..#.#....",
+        ]);
+
+        assert_eq!(read_letters(&screen), "HERP");
+    }
+
+    #[test]
+    fn unknown_letters() {
+        let screen = screen(&[
+            "####.####",
+            "####.#..#",
+            "####.#..#",
+            "####.#..#",
+            "####.#..#",
+            "####.####",
+        ]);
+
+        assert_eq!(read_letters(&screen), "??");
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    fmt::Write as _,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use ndarray::Array2;
+
+use super::{
+    common::{
+        blend_on_black, channel_collector, create_file, frame_to_ansi, map_to_image,
+        ChannelCollector, FrameEncoding, FrameReceiver,
+    },
+    RenderError, ToChar, ToColor,
+};
+
+/// A frame as terminal output, together with its size in characters.
+pub struct TextFrame {
+    text: String,
+    size: (usize, usize),
+}
+
+/// Encodes frames as terminal output.
+pub struct CastEncoding;
+
+impl FrameEncoding for CastEncoding {
+    type Frame = TextFrame;
+
+    /// Draws the frame with colored half blocks, two cells per character.
+    fn encode(&self, data: &Array2<impl ToColor>) -> (TextFrame, (usize, usize)) {
+        let image = map_to_image(data, 1);
+        let size = (image.width(), image.height().div_ceil(2));
+        let text = frame_to_ansi(image);
+        (TextFrame { text, size }, size)
+    }
+
+    /// Draws the frame with colored characters, one per cell.
+    fn encode_text(&self, data: &Array2<impl ToColor + ToChar>) -> (TextFrame, (usize, usize)) {
+        let (rows, cols) = data.dim();
+        let size = (cols, rows);
+        let text = frame_to_text(data);
+        (TextFrame { text, size }, size)
+    }
+}
+
+pub type CastCollector = ChannelCollector<CastEncoding>;
+
+/// Writes the frames as an [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/) file.
+pub struct CastWriter {
+    sleep_time: f64,
+    receiver: FrameReceiver<TextFrame>,
+}
+
+/// Creates a collector and writer for asciinema recordings.
+///
+/// # Arguments
+///
+/// * `sleep_time` - How long the last frame stays visible, in seconds.
+pub fn create_cast(sleep_time: f64) -> (CastCollector, CastWriter) {
+    let (collector, receiver) = channel_collector(CastEncoding);
+    (
+        collector,
+        CastWriter {
+            sleep_time,
+            receiver,
+        },
+    )
+}
+
+fn frame_to_text(data: &Array2<impl ToColor + ToChar>) -> String {
+    let mut text = String::new();
+    for row in data.rows() {
+        // Only switch colors when necessary, to keep the recording small and diffable
+        let mut current_color = None;
+        for cell in row {
+            let color = blend_on_black(cell.to_color());
+            if current_color != Some(color) {
+                let (r, g, b) = color;
+                let _ = write!(text, "\x1b[38;2;{};{};{}m", r, g, b);
+                current_color = Some(color);
+            }
+            text.push(cell.to_char());
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text
+}
+
+/// Encodes text as a JSON string literal.
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn output_event(timestamp: f64, data: &str) -> String {
+    format!(
+        "[{:.6}, \"o\", {}]\n",
+        timestamp.max(0.0),
+        json_string(data)
+    )
+}
+
+impl super::Writer for CastWriter {
+    fn write(
+        self,
+        filename: &impl AsRef<Path>,
+        _frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError> {
+        let path = filename.as_ref();
+        let io_error = |source| RenderError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let mut file = BufWriter::new(create_file(path)?);
+
+        let mut last_timestamp = None;
+        for (frame, timestamp) in self.receiver.iter() {
+            if last_timestamp.is_none() {
+                // One more line than the frame, for the cursor after the last line
+                let (width, height) = frame.size;
+                let header = format!(
+                    "{{\"version\": 2, \"width\": {}, \"height\": {}}}\n",
+                    width,
+                    height + 1
+                );
+                file.write_all(header.as_bytes()).map_err(io_error)?;
+                file.write_all(output_event(0.0, "\x1b[?25l\x1b[2J").as_bytes())
+                    .map_err(io_error)?;
+            }
+
+            let data = format!("\x1b[H{}", frame.text.replace('\n', "\r\n"));
+            file.write_all(output_event(timestamp, &data).as_bytes())
+                .map_err(io_error)?;
+            last_timestamp = Some(timestamp);
+        }
+
+        if let Some(last_timestamp) = last_timestamp {
+            // Players stop at the last event, so a final one keeps the last frame visible
+            let end = output_event(last_timestamp + self.sleep_time, "\x1b[?25h");
+            file.write_all(end.as_bytes()).map_err(io_error)?;
+        }
+        file.flush().map_err(io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rgb::RGBA8;
+
+    use super::*;
+    use crate::helpers::rendering::{common::testing::record, Collector};
+
+    #[derive(Clone, Copy)]
+    enum Cell {
+        Wall,
+        Floor,
+    }
+
+    impl ToColor for Cell {
+        fn to_color(&self) -> RGBA8 {
+            match self {
+                Cell::Wall => RGBA8::new(255, 255, 255, 255),
+                Cell::Floor => RGBA8::new(0, 0, 0, 255),
+            }
+        }
+    }
+
+    impl ToChar for Cell {
+        fn to_char(&self) -> char {
+            match self {
+                Cell::Wall => '#',
+                Cell::Floor => '.',
+            }
+        }
+    }
+
+    #[test]
+    fn json_strings() {
+        assert_eq!(
+            json_string("a\"b\\c\r\n\x1b[H▀"),
+            "\"a\\\"b\\\\c\\r\\n\\u001b[H▀\""
+        );
+    }
+
+    #[test]
+    fn text_frames() {
+        let data = Array2::from_shape_vec(
+            (2, 3),
+            vec![
+                Cell::Wall,
+                Cell::Wall,
+                Cell::Floor,
+                Cell::Floor,
+                Cell::Floor,
+                Cell::Wall,
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            frame_to_text(&data),
+            "\x1b[38;2;255;255;255m##\x1b[38;2;0;0;0m.\x1b[0m\n\
+             \x1b[38;2;0;0;0m..\x1b[38;2;255;255;255m#\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn writes_asciicast() {
+        let path = std::env::temp_dir().join(format!("aoc_cast_{}.cast", std::process::id()));
+        let (collector, writer) = create_cast(2.0);
+
+        let (written, collected) = record(collector, writer, &path, None, |collector| {
+            let data = Array2::from_elem((2, 3), Cell::Wall);
+            collector.add_text_frame(&data, 0.0)?;
+            collector.add_text_frame(&data, 0.5)?;
+            assert!(matches!(
+                collector.add_frame(&data, 1.0),
+                Err(RenderError::FrameSize { .. })
+            ));
+            Ok(())
+        });
+        written.unwrap();
+        collected.unwrap();
+
+        let cast = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines = cast.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "{\"version\": 2, \"width\": 3, \"height\": 3}");
+        assert!(lines[2].starts_with("[0.000000, \"o\", \"\\u001b[H\\u001b[38;2;255;255;255m###"));
+        assert!(lines[3].starts_with("[0.500000, \"o\", "));
+        assert_eq!(lines[4], "[2.500000, \"o\", \"\\u001b[?25h\"]");
+    }
+}
@@ -1,8 +1,28 @@
+use std::{
+    fmt::Write,
+    fs::File,
+    path::Path,
+    sync::mpsc::{Receiver, SyncSender},
+};
+
 use imgref::ImgVec;
 use ndarray::{Array2, Axis};
 use rgb::RGBA8;
 
-pub fn map_to_image(map: &Array2<impl super::ToColor>, scale: usize) -> ImgVec<RGBA8> {
+use super::{Collector, RenderError, ToChar, ToColor};
+
+/// Creates the output file of a writer.
+///
+/// Writers call this before they receive the first frame, so that an unusable path is reported
+/// right away, instead of after the collector has produced all frames in vain.
+pub fn create_file(path: &Path) -> Result<File, RenderError> {
+    File::create(path).map_err(|source| RenderError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+pub fn map_to_image(map: &Array2<impl ToColor>, scale: usize) -> ImgVec<RGBA8> {
     let mut pixels = vec![];
 
     for row in map.axis_iter(Axis(0)) {
@@ -20,3 +40,206 @@ pub fn map_to_image(map: &Array2<impl super::ToColor>, scale: usize) -> ImgVec<R
 
     ImgVec::<RGBA8>::new(pixels, map.dim().1 * scale, map.dim().0 * scale)
 }
+
+/// The color of a pixel on a black background, for outputs without transparency.
+pub fn blend_on_black(pixel: RGBA8) -> (u8, u8, u8) {
+    let blend = |value: u8| (u16::from(value) * u16::from(pixel.a) / 255) as u8;
+    (blend(pixel.r), blend(pixel.g), blend(pixel.b))
+}
+
+/// Draws an image with 24-bit colored half blocks, two pixels per character.
+pub fn frame_to_ansi(image: ImgVec<RGBA8>) -> String {
+    let mut text = String::new();
+    for y in (0..image.height()).step_by(2) {
+        for x in 0..image.width() {
+            let (r, g, b) = blend_on_black(image[(x, y)]);
+            let _ = write!(text, "\x1b[38;2;{};{};{}m", r, g, b);
+            if y + 1 < image.height() {
+                let (r, g, b) = blend_on_black(image[(x, y + 1)]);
+                let _ = write!(text, "\x1b[48;2;{};{};{}m", r, g, b);
+            } else {
+                text.push_str("\x1b[49m");
+            }
+            text.push('▀');
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text
+}
+
+/// Escapes text for use in HTML or XML documents, including attribute values.
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Makes sure that all frames of an animation have the same size as the first one.
+#[derive(Default)]
+pub struct FrameSize {
+    size: Option<(usize, usize)>,
+}
+
+impl FrameSize {
+    pub fn check(&mut self, frame: usize, image: &ImgVec<RGBA8>) -> Result<(), RenderError> {
+        self.check_dimensions(frame, (image.width(), image.height()))
+    }
+
+    pub fn check_dimensions(
+        &mut self,
+        frame: usize,
+        actual: (usize, usize),
+    ) -> Result<(), RenderError> {
+        match self.size {
+            Some(expected) if expected != actual => Err(RenderError::FrameSize {
+                frame,
+                expected,
+                actual,
+            }),
+            _ => {
+                self.size = Some(actual);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Turns the cells of a frame into what the writer of a [`ChannelCollector`] receives.
+pub trait FrameEncoding {
+    type Frame: Send;
+
+    /// The encoded frame, together with its size, which has to be the same for all frames.
+    fn encode(&self, data: &Array2<impl ToColor>) -> (Self::Frame, (usize, usize));
+
+    /// Like [`FrameEncoding::encode`], for cells that can also be displayed as characters.
+    fn encode_text(&self, data: &Array2<impl ToColor + ToChar>) -> (Self::Frame, (usize, usize)) {
+        self.encode(data)
+    }
+}
+
+/// Encodes frames as images, with a block of `scale` x `scale` pixels per cell.
+pub struct Pixels {
+    pub scale: usize,
+}
+
+impl FrameEncoding for Pixels {
+    type Frame = ImgVec<RGBA8>;
+
+    fn encode(&self, data: &Array2<impl ToColor>) -> (ImgVec<RGBA8>, (usize, usize)) {
+        let image = map_to_image(data, self.scale);
+        let size = (image.width(), image.height());
+        (image, size)
+    }
+}
+
+/// The collector of all formats whose writers work on images.
+pub type PixelCollector = ChannelCollector<Pixels>;
+
+/// The frames a writer receives from a [`ChannelCollector`], with their timestamps.
+pub type FrameReceiver<F> = Receiver<(F, f64)>;
+
+/// A [`Collector`] that encodes every frame and sends it to its writer.
+///
+/// The channel only holds a few frames, so the collector blocks until the writer catches up.
+pub struct ChannelCollector<E: FrameEncoding> {
+    encoding: E,
+    frames_count: usize,
+    frame_size: FrameSize,
+    sender: SyncSender<(E::Frame, f64)>,
+}
+
+/// Creates a collector together with the receiving end for its writer.
+pub fn channel_collector<E: FrameEncoding>(
+    encoding: E,
+) -> (ChannelCollector<E>, FrameReceiver<E::Frame>) {
+    let (sender, receiver) = std::sync::mpsc::sync_channel(4);
+    (
+        ChannelCollector {
+            encoding,
+            frames_count: 0,
+            frame_size: FrameSize::default(),
+            sender,
+        },
+        receiver,
+    )
+}
+
+impl<E: FrameEncoding> ChannelCollector<E> {
+    fn send(
+        &mut self,
+        (frame, size): (E::Frame, (usize, usize)),
+        timestamp: f64,
+    ) -> Result<(), RenderError> {
+        self.frame_size.check_dimensions(self.frames_count, size)?;
+        self.sender
+            .send((frame, timestamp))
+            .map_err(|_| RenderError::WriterStopped)?;
+        self.frames_count += 1;
+        Ok(())
+    }
+}
+
+impl<E: FrameEncoding> Collector for ChannelCollector<E> {
+    fn add_frame(
+        &mut self,
+        data: &Array2<impl ToColor>,
+        timestamp: f64,
+    ) -> Result<(), RenderError> {
+        let frame = self.encoding.encode(data);
+        self.send(frame, timestamp)
+    }
+
+    fn add_text_frame(
+        &mut self,
+        data: &Array2<impl ToColor + ToChar>,
+        timestamp: f64,
+    ) -> Result<(), RenderError> {
+        let frame = self.encoding.encode_text(data);
+        self.send(frame, timestamp)
+    }
+
+    fn get_num_frames(&self) -> usize {
+        self.frames_count
+    }
+}
+
+/// Shared by the tests of the individual writers.
+#[cfg(test)]
+pub mod testing {
+    use std::path::Path;
+
+    use rgb::RGBA8;
+
+    use super::super::{Collector, RenderError, ToColor, Writer};
+
+    /// A cell of a test frame, drawn in the given shade of gray.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Gray(pub u8);
+
+    impl ToColor for Gray {
+        fn to_color(&self) -> RGBA8 {
+            RGBA8::new(self.0, self.0, self.0, 255)
+        }
+    }
+
+    /// Runs the writer on its own thread, while `collect` adds frames to the collector.
+    ///
+    /// # Returns
+    ///
+    /// The result of the writer and the result of `collect`.
+    pub fn record<C: Collector>(
+        mut collector: C,
+        writer: impl Writer + Send,
+        path: &Path,
+        frame_count_hint: Option<usize>,
+        collect: impl FnOnce(&mut C) -> Result<(), RenderError>,
+    ) -> (Result<(), RenderError>, Result<(), RenderError>) {
+        std::thread::scope(|s| {
+            let writer_thread = s.spawn(|| writer.write(&path, frame_count_hint));
+            let collected = collect(&mut collector);
+            drop(collector);
+            (writer_thread.join().unwrap(), collected)
+        })
+    }
+}
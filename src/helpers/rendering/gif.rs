@@ -1,12 +1,16 @@
-use std::fs::File;
-
 use ndarray::Array2;
 
+use super::{
+    common::{create_file, FrameSize},
+    RenderError,
+};
+
 pub struct GifskyCollector {
     collector: gifski::Collector,
     scale: usize,
     sleep_time: f64,
     frames_count: usize,
+    frame_size: FrameSize,
 }
 
 pub struct GifskyWriter {
@@ -14,46 +18,65 @@ pub struct GifskyWriter {
 }
 
 impl super::Writer for GifskyWriter {
-    fn write(self, filename: &impl AsRef<std::path::Path>, frame_count_hint: Option<usize>) {
-        let file = File::create(filename).unwrap();
+    fn write(
+        self,
+        filename: &impl AsRef<std::path::Path>,
+        frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError> {
+        let path = filename.as_ref();
+        let file = create_file(path)?;
         if let Some(frame_count_hint) = frame_count_hint {
             let mut progress = gifski::progress::ProgressBar::new(frame_count_hint as u64);
-            self.writer.write(&file, &mut progress).unwrap();
+            self.writer.write(&file, &mut progress)?;
         } else {
             let mut progress = gifski::progress::NoProgress {};
-            self.writer.write(&file, &mut progress).unwrap();
+            self.writer.write(&file, &mut progress)?;
         }
+        Ok(())
     }
 }
 
-pub fn create_gifski(scale: usize, sleep_time: f64) -> (impl super::Collector, impl super::Writer) {
+pub fn create_gifski(
+    scale: usize,
+    sleep_time: f64,
+) -> Result<(GifskyCollector, GifskyWriter), RenderError> {
     let (collector, writer) = gifski::new(gifski::Settings {
         quality: 100,
         fast: false,
         repeat: gifski::Repeat::Infinite,
         width: None,
         height: None,
-    })
-    .unwrap();
+    })?;
 
-    (
+    Ok((
         GifskyCollector {
             collector,
             scale,
             sleep_time,
             frames_count: 0,
+            frame_size: FrameSize::default(),
         },
         GifskyWriter { writer },
-    )
+    ))
 }
 
 impl super::Collector for GifskyCollector {
-    fn add_frame(&mut self, data: &Array2<impl super::ToColor>, timestamp: f64) {
+    fn add_frame(
+        &mut self,
+        data: &Array2<impl super::ToColor>,
+        timestamp: f64,
+    ) -> Result<(), RenderError> {
         let img = super::common::map_to_image(data, self.scale);
+        self.frame_size.check(self.frames_count, &img)?;
         self.collector
             .add_frame_rgba(self.frames_count, img, timestamp + self.sleep_time)
-            .unwrap();
+            .map_err(|err| match err {
+                // The writer has dropped its end of the queue
+                gifski::Error::ThreadSend | gifski::Error::Aborted => RenderError::WriterStopped,
+                err => RenderError::Gif(err),
+            })?;
         self.frames_count += 1;
+        Ok(())
     }
     fn get_num_frames(&self) -> usize {
         self.frames_count
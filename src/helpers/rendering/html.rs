@@ -0,0 +1,225 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use imgref::ImgVec;
+use indicatif::ProgressBar;
+use rgb::RGBA8;
+
+use super::{
+    common::{channel_collector, create_file, escape_xml, FrameReceiver, PixelCollector, Pixels},
+    RenderError,
+};
+
+/// The page with the player; the animation data replaces [`DATA_MARKER`].
+const PLAYER: &str = include_str!("player.html");
+const DATA_MARKER: &str = "/* animation */";
+
+/// Writes the frames into a single HTML file with an embedded player.
+///
+/// Frames are stored with one pixel per cell and only get scaled up for display.
+pub struct HtmlWriter {
+    scale: usize,
+    sleep_time: f64,
+    receiver: FrameReceiver<ImgVec<RGBA8>>,
+}
+
+pub fn create_html(scale: usize, sleep_time: f64) -> (PixelCollector, HtmlWriter) {
+    let (collector, receiver) = channel_collector(Pixels { scale: 1 });
+    (
+        collector,
+        HtmlWriter {
+            scale,
+            sleep_time,
+            receiver,
+        },
+    )
+}
+
+/// The colors of all frames, shared between them.
+#[derive(Default)]
+struct Palette {
+    colors: Vec<RGBA8>,
+    indices: HashMap<RGBA8, usize>,
+}
+
+impl Palette {
+    fn index(&mut self, color: RGBA8) -> usize {
+        *self.indices.entry(color).or_insert_with(|| {
+            self.colors.push(color);
+            self.colors.len() - 1
+        })
+    }
+
+    fn to_json(&self) -> String {
+        let colors = self
+            .colors
+            .iter()
+            .map(|color| format!("[{},{},{},{}]", color.r, color.g, color.b, color.a))
+            .collect::<Vec<_>>();
+        format!("[{}]", colors.join(","))
+    }
+}
+
+/// Encodes the pixels of an image, row by row, as pairs of run length and palette index.
+fn run_lengths(image: &ImgVec<RGBA8>, palette: &mut Palette) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = vec![];
+    for pixel in image.pixels() {
+        let index = palette.index(pixel);
+        match runs.last_mut() {
+            Some((length, last_index)) if *last_index == index => *length += 1,
+            _ => runs.push((1, index)),
+        }
+    }
+    runs
+}
+
+fn frame_to_json(timestamp: f64, runs: &[(usize, usize)]) -> String {
+    let mut json = format!("{{\"t\":{:.6},\"runs\":[", timestamp);
+    for (i, (length, index)) in runs.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let _ = write!(json, "{}{},{}", separator, length, index);
+    }
+    json.push_str("]}");
+    json
+}
+
+impl super::Writer for HtmlWriter {
+    fn write(
+        self,
+        filename: &impl AsRef<Path>,
+        frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError> {
+        let path = filename.as_ref();
+        let io_error = |source| RenderError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let mut file = BufWriter::new(create_file(path)?);
+
+        let title = path
+            .file_stem()
+            .map_or("Animation".into(), |stem| stem.to_string_lossy());
+        let (head, tail) = PLAYER
+            .split_once(DATA_MARKER)
+            .expect("The player has a marker for the animation data");
+        let head = head.replacen(
+            "<title>Animation</title>",
+            &format!("<title>{}</title>", escape_xml(&title)),
+            1,
+        );
+
+        // Frames are written as they arrive; the palette is only complete after the last one
+        let progress = ProgressBar::new(frame_count_hint.unwrap_or(0) as u64);
+        let mut palette = Palette::default();
+        let mut last_frame = None;
+        writeln!(file, "{}const frames = [", head).map_err(io_error)?;
+        for (frame, timestamp) in self.receiver.iter() {
+            let runs = run_lengths(&frame, &mut palette);
+            writeln!(file, "{},", frame_to_json(timestamp, &runs)).map_err(io_error)?;
+            last_frame = Some((frame.width(), frame.height(), timestamp));
+            progress.inc(1);
+        }
+
+        // Without frames, the player shows an empty animation
+        let (width, height, end) = last_frame.map_or((0, 0, 0.0), |(width, height, timestamp)| {
+            (width, height, timestamp + self.sleep_time)
+        });
+        write!(
+            file,
+            "];\nconst animation = {{\"width\":{},\"height\":{},\"scale\":{},\"end\":{:.6},\"palette\":{}}};{}",
+            width,
+            height,
+            self.scale,
+            end,
+            palette.to_json(),
+            tail
+        )
+        .map_err(io_error)?;
+        file.flush().map_err(io_error)?;
+        progress.finish();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::helpers::rendering::{
+        common::testing::{record, Gray},
+        Collector, Writer,
+    };
+
+    const RED: RGBA8 = RGBA8::new(255, 0, 0, 255);
+    const BLUE: RGBA8 = RGBA8::new(0, 0, 255, 255);
+
+    #[test]
+    fn encodes_runs() {
+        let mut palette = Palette::default();
+        let image = ImgVec::new(vec![RED, RED, BLUE, BLUE, BLUE, RED], 3, 2);
+
+        let runs = run_lengths(&image, &mut palette);
+
+        assert_eq!(runs, [(2, 0), (3, 1), (1, 0)]);
+        assert_eq!(
+            frame_to_json(0.5, &runs),
+            "{\"t\":0.500000,\"runs\":[2,0,3,1,1,0]}"
+        );
+        assert_eq!(palette.to_json(), "[[255,0,0,255],[0,0,255,255]]");
+    }
+
+    #[test]
+    fn writes_empty_player() {
+        let path = std::env::temp_dir().join(format!("aoc_empty_{}.html", std::process::id()));
+        let (collector, writer) = create_html(8, 2.0);
+        drop(collector);
+        writer.write(&path, Some(0)).unwrap();
+
+        let html = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(html.contains(
+            "const frames = [\n\
+             ];\n\
+             const animation = {\"width\":0,\"height\":0,\"scale\":8,\"end\":0.000000,\
+             \"palette\":[]};\n"
+        ));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn writes_player() {
+        let path = std::env::temp_dir().join(format!("aoc_player_{}.html", std::process::id()));
+        let (collector, writer) = create_html(8, 2.0);
+
+        let (written, collected) = record(collector, writer, &path, None, |collector| {
+            for (shade, timestamp) in [(255, 0.0), (0, 0.25)] {
+                let data = Array2::from_shape_fn((2, 3), |_| Gray(shade));
+                collector.add_frame(&data, timestamp)?;
+            }
+            Ok(())
+        });
+        written.unwrap();
+        collected.unwrap();
+
+        let html = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(html.contains(&format!("<title>aoc_player_{}</title>", std::process::id())));
+        assert!(html.contains(
+            "const frames = [\n\
+             {\"t\":0.000000,\"runs\":[6,0]},\n\
+             {\"t\":0.250000,\"runs\":[6,1]},\n\
+             ];\n\
+             const animation = {\"width\":3,\"height\":2,\"scale\":8,\"end\":2.250000,\
+             \"palette\":[[255,255,255,255],[0,0,0,255]]};\n"
+        ));
+        assert!(!html.contains(DATA_MARKER));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+}
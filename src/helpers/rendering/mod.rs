@@ -1,32 +1,127 @@
-use std::path::Path;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
 
 use ndarray::Array2;
 use rgb::RGBA8;
+use thiserror::Error;
 
+mod cast;
 mod common;
 mod gif;
+mod html;
+pub mod palette;
+mod png;
+mod recording;
+mod svg;
+mod terminal;
 mod webp;
+mod y4m;
+
+#[cfg(test)]
+pub use common::testing;
+pub use recording::{Artifact, Recording, RecordingSettings};
+pub use svg::SvgOptions;
+pub use terminal::Fit;
 
 pub trait ToColor {
     fn to_color(&self) -> RGBA8;
 }
 
+/// Frames that are already colored, e.g. by a [`palette::Heatmap`].
+impl ToColor for RGBA8 {
+    fn to_color(&self) -> RGBA8 {
+        *self
+    }
+}
+
+/// The character that represents a cell in text based renderings.
+///
+/// Should be exactly one column wide in a terminal.
+pub trait ToChar {
+    fn to_char(&self) -> char;
+}
+
+#[derive(Error, Debug)]
+pub enum RenderError {
+    #[error("Unable to write '{}'", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("GIF encoder failed")]
+    Gif(#[from] gifski::Error),
+    #[error("WebP encoder failed")]
+    Webp(#[from] webp_animation::Error),
+    #[error("PNG encoder failed")]
+    Png(#[from] ::png::EncodingError),
+    #[error("Unable to write to the terminal")]
+    Terminal(#[source] io::Error),
+    #[error("Frame {frame} has a size of {actual:?}, expected {expected:?}")]
+    FrameSize {
+        frame: usize,
+        expected: (usize, usize),
+        actual: (usize, usize),
+    },
+    #[error("Expected {expected} frames, got {actual}")]
+    FrameCount { expected: usize, actual: usize },
+    /// The writer stopped accepting frames; the reason is returned by [`Writer::write`].
+    #[error("The writer stopped before all frames were collected")]
+    WriterStopped,
+}
+
 pub trait Writer {
-    fn write(self, filename: &impl AsRef<Path>, frame_count_hint: Option<usize>);
+    /// Writes all frames that the collector receives to a file.
+    ///
+    /// Returns once the collector is dropped and all frames are written, or
+    /// as soon as an error occurs; the collector then fails with [`RenderError::WriterStopped`].
+    fn write(
+        self,
+        filename: &impl AsRef<Path>,
+        frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError>;
 }
 
 pub trait Collector {
-    fn add_frame(&mut self, data: &Array2<impl ToColor>, timestamp: f64);
+    fn add_frame(&mut self, data: &Array2<impl ToColor>, timestamp: f64)
+        -> Result<(), RenderError>;
+
+    /// Like [`Collector::add_frame`], for cells that can also be displayed as characters.
+    ///
+    /// Text based collectors draw the characters instead of blocks of color;
+    /// all others ignore them.
+    fn add_text_frame(
+        &mut self,
+        data: &Array2<impl ToColor + ToChar>,
+        timestamp: f64,
+    ) -> Result<(), RenderError> {
+        self.add_frame(data, timestamp)
+    }
+
     fn get_num_frames(&self) -> usize;
 }
 
+/// Something that can be added to a [`Collector`] as a single frame.
+pub trait Renderable {
+    fn add_to(&self, collector: &mut impl Collector, timestamp: f64) -> Result<(), RenderError>;
+}
+
+impl<T: ToColor> Renderable for Array2<T> {
+    fn add_to(&self, collector: &mut impl Collector, timestamp: f64) -> Result<(), RenderError> {
+        collector.add_frame(self, timestamp)
+    }
+}
+
 pub struct FramesCounter {
     count: usize,
 }
 
 impl Collector for FramesCounter {
-    fn add_frame(&mut self, _data: &Array2<impl ToColor>, _timestamp: f64) {
+    fn add_frame(
+        &mut self,
+        _data: &Array2<impl ToColor>,
+        _timestamp: f64,
+    ) -> Result<(), RenderError> {
         self.count += 1;
+        Ok(())
     }
     fn get_num_frames(&self) -> usize {
         self.count
@@ -45,16 +140,455 @@ impl Default for FramesCounter {
     }
 }
 
+/// The file formats a rendering can be written in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    #[default]
+    Gif,
+    Webp,
+    /// An animated PNG
+    Apng,
+    /// A directory with one PNG per frame, e.g. for video tools
+    PngFrames,
+    /// A vector graphic, animated if there is more than one frame
+    Svg,
+    /// A directory with one static SVG per frame
+    SvgFrames,
+    /// A web page with a player that can pause, scrub and step through the animation
+    Html,
+    /// An uncompressed YUV4MPEG2 video, e.g. to pipe into a video encoder with `--output -`
+    Y4m,
+    /// Plays the animation in the terminal instead of writing a file
+    Terminal,
+    /// An asciinema recording
+    Cast,
+}
+
+impl Format {
+    /// The extension of the files in this format, or `None` if the output has none.
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Format::Gif => Some("gif"),
+            Format::Webp => Some("webp"),
+            Format::Apng => Some("png"),
+            Format::PngFrames => None,
+            Format::Svg => Some("svg"),
+            Format::SvgFrames => None,
+            Format::Html => Some("html"),
+            Format::Y4m => Some("y4m"),
+            Format::Terminal => None,
+            Format::Cast => Some("cast"),
+        }
+    }
+
+    /// Whether the rendering gets written to the output path, as a file or directory.
+    pub fn writes_output(self) -> bool {
+        self != Format::Terminal
+    }
+}
+
+/// Controls how a renderer writes its animation.
+///
+/// Every setting that isn't specified falls back to a default chosen by the renderer.
+#[derive(Debug, Default, Clone, clap::Args)]
+pub struct RenderOptions {
+    /// The file format of the rendering
+    #[arg(long, value_enum, default_value_t = Format::Gif)]
+    pub format: Format,
+
+    /// The size of a single cell, in pixels
+    #[arg(long, value_parser = parse_scale)]
+    pub scale: Option<usize>,
+
+    /// The number of frames per second
+    #[arg(long, conflicts_with = "frame_delay", value_parser = parse_positive)]
+    pub fps: Option<f64>,
+
+    /// The time between two frames, in seconds
+    #[arg(long, value_parser = parse_positive)]
+    pub frame_delay: Option<f64>,
+
+    /// How long the last frame stays visible, in seconds
+    #[arg(long, value_parser = parse_non_negative)]
+    pub hold_last: Option<f64>,
+
+    /// The path of the rendering; defaults to the name of the task
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Only print the last frame when rendering to the terminal, e.g. for logs
+    #[arg(long)]
+    pub non_interactive: bool,
+
+    /// How frames that don't fit into the terminal are displayed
+    #[arg(long, value_enum, default_value_t = Fit::Scale)]
+    pub fit: Fit,
+
+    /// Draw lines between the cells of SVG renderings
+    #[arg(long)]
+    pub grid: bool,
+
+    /// Label the cells of SVG renderings, if the renderer supports it
+    #[arg(long)]
+    pub labels: bool,
+}
+
+fn parse_scale(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(scale) if scale >= 1 => Ok(scale),
+        Ok(_) => Err("must be at least 1".into()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_positive(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(number) if number.is_finite() && number > 0.0 => Ok(number),
+        Ok(_) => Err("must be a number greater than 0".into()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_non_negative(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(number) if number.is_finite() && number >= 0.0 => Ok(number),
+        Ok(_) => Err("must be a number of at least 0".into()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// The settings a renderer uses for everything that isn't specified in its [`RenderOptions`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderDefaults {
+    pub scale: usize,
+    pub frame_delay: f64,
+    pub hold_last: f64,
+}
+
+#[allow(dead_code)]
+impl RenderOptions {
+    /// Combines the options with the defaults of a renderer.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The file name to use if no output path was given, without extension.
+    /// * `defaults` - The settings of the renderer for everything that wasn't specified.
+    pub fn settings(&self, name: &str, defaults: RenderDefaults) -> RecordingSettings {
+        RecordingSettings {
+            format: self.format,
+            path: self.output_path(name),
+            scale: self.scale.unwrap_or(defaults.scale),
+            frame_delay: self.frame_delay().unwrap_or(defaults.frame_delay),
+            hold_last: self.hold_last.unwrap_or(defaults.hold_last),
+            interactive: !self.non_interactive,
+            fit: self.fit,
+            svg: SvgOptions {
+                grid: self.grid,
+                labels: self.labels,
+            },
+        }
+    }
+
+    /// The time between two frames, in seconds, as given by either `--frame-delay` or `--fps`.
+    pub fn frame_delay(&self) -> Option<f64> {
+        self.frame_delay.or(self.fps.map(|fps| 1.0 / fps))
+    }
+
+    /// Whether the rendering gets written to the standard output instead of a file.
+    pub fn writes_to_stdout(&self) -> bool {
+        self.format == Format::Y4m && self.output.as_deref() == Some(Path::new(y4m::STDOUT_PATH))
+    }
+
+    /// The path the rendering should be written to.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The file name to use if no output path was given, without extension.
+    pub fn output_path(&self, name: &str) -> PathBuf {
+        self.output.clone().unwrap_or_else(|| {
+            let mut path = PathBuf::from(name);
+            if let Some(extension) = self.format.extension() {
+                path.set_extension(extension);
+            }
+            path
+        })
+    }
+}
+
+/// A [`Collector`] for any of the supported formats, see [`Renderers::create_renderer`].
+pub enum FormatCollector {
+    Gif(gif::GifskyCollector),
+    Pixels(common::PixelCollector),
+    Svg(svg::SvgCollector),
+    Cast(cast::CastCollector),
+}
+
+impl Collector for FormatCollector {
+    fn add_frame(
+        &mut self,
+        data: &Array2<impl ToColor>,
+        timestamp: f64,
+    ) -> Result<(), RenderError> {
+        match self {
+            FormatCollector::Gif(collector) => collector.add_frame(data, timestamp),
+            FormatCollector::Pixels(collector) => collector.add_frame(data, timestamp),
+            FormatCollector::Svg(collector) => collector.add_frame(data, timestamp),
+            FormatCollector::Cast(collector) => collector.add_frame(data, timestamp),
+        }
+    }
+    fn add_text_frame(
+        &mut self,
+        data: &Array2<impl ToColor + ToChar>,
+        timestamp: f64,
+    ) -> Result<(), RenderError> {
+        match self {
+            FormatCollector::Cast(collector) => collector.add_text_frame(data, timestamp),
+            FormatCollector::Svg(collector) => collector.add_text_frame(data, timestamp),
+            _ => self.add_frame(data, timestamp),
+        }
+    }
+    fn get_num_frames(&self) -> usize {
+        match self {
+            FormatCollector::Gif(collector) => collector.get_num_frames(),
+            FormatCollector::Pixels(collector) => collector.get_num_frames(),
+            FormatCollector::Svg(collector) => collector.get_num_frames(),
+            FormatCollector::Cast(collector) => collector.get_num_frames(),
+        }
+    }
+}
+
+/// A [`Writer`] for any of the supported formats, see [`Renderers::create_renderer`].
+pub enum FormatWriter {
+    Gif(gif::GifskyWriter),
+    Webp(webp::WebpWriter),
+    Apng(png::ApngWriter),
+    PngFrames(png::PngFramesWriter),
+    Svg(svg::SvgWriter),
+    SvgFrames(svg::SvgFramesWriter),
+    Html(html::HtmlWriter),
+    Y4m(y4m::Y4mWriter),
+    Terminal(terminal::TerminalWriter),
+    Cast(cast::CastWriter),
+}
+
+impl Writer for FormatWriter {
+    fn write(
+        self,
+        filename: &impl AsRef<Path>,
+        frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError> {
+        match self {
+            FormatWriter::Gif(writer) => writer.write(filename, frame_count_hint),
+            FormatWriter::Webp(writer) => writer.write(filename, frame_count_hint),
+            FormatWriter::Apng(writer) => writer.write(filename, frame_count_hint),
+            FormatWriter::PngFrames(writer) => writer.write(filename, frame_count_hint),
+            FormatWriter::Svg(writer) => writer.write(filename, frame_count_hint),
+            FormatWriter::SvgFrames(writer) => writer.write(filename, frame_count_hint),
+            FormatWriter::Html(writer) => writer.write(filename, frame_count_hint),
+            FormatWriter::Y4m(writer) => writer.write(filename, frame_count_hint),
+            FormatWriter::Terminal(writer) => writer.write(filename, frame_count_hint),
+            FormatWriter::Cast(writer) => writer.write(filename, frame_count_hint),
+        }
+    }
+}
+
+/// Creates pairs of [`Collector`] and [`Writer`].
+///
+/// The writer has to run on its own thread while frames are collected;
+/// [`Recording`] takes care of that.
 pub struct Renderers {
     _inconstructible: (),
 }
 
 #[allow(dead_code)]
 impl Renderers {
-    pub fn create_gif_renderer(scale: usize, sleep_time: f64) -> (impl Collector, impl Writer) {
-        self::gif::create_gifski(scale, sleep_time)
+    /// Creates a collector and writer for the format given in the settings.
+    pub fn create_renderer(
+        settings: &RecordingSettings,
+    ) -> Result<(FormatCollector, FormatWriter), RenderError> {
+        let (scale, sleep_time) = (settings.scale, settings.hold_last);
+        Ok(match settings.format {
+            Format::Gif => {
+                let (collector, writer) = self::gif::create_gifski(scale, sleep_time)?;
+                (FormatCollector::Gif(collector), FormatWriter::Gif(writer))
+            }
+            Format::Webp => {
+                let (collector, writer) = self::webp::create_webp(scale, sleep_time);
+                (
+                    FormatCollector::Pixels(collector),
+                    FormatWriter::Webp(writer),
+                )
+            }
+            Format::Apng => {
+                let (collector, writer) = self::png::create_apng(scale, sleep_time);
+                (
+                    FormatCollector::Pixels(collector),
+                    FormatWriter::Apng(writer),
+                )
+            }
+            Format::PngFrames => {
+                let (collector, writer) = self::png::create_png_frames(scale, sleep_time);
+                (
+                    FormatCollector::Pixels(collector),
+                    FormatWriter::PngFrames(writer),
+                )
+            }
+            Format::Svg => {
+                let (collector, writer) = self::svg::create_svg(scale, sleep_time, settings.svg);
+                (FormatCollector::Svg(collector), FormatWriter::Svg(writer))
+            }
+            Format::SvgFrames => {
+                let (collector, writer) = self::svg::create_svg_frames(scale, settings.svg);
+                (
+                    FormatCollector::Svg(collector),
+                    FormatWriter::SvgFrames(writer),
+                )
+            }
+            Format::Html => {
+                let (collector, writer) = self::html::create_html(scale, sleep_time);
+                (
+                    FormatCollector::Pixels(collector),
+                    FormatWriter::Html(writer),
+                )
+            }
+            Format::Y4m => {
+                let (collector, writer) = self::y4m::create_y4m(scale, sleep_time);
+                (
+                    FormatCollector::Pixels(collector),
+                    FormatWriter::Y4m(writer),
+                )
+            }
+            Format::Terminal => {
+                let (collector, writer) = self::terminal::create_terminal(
+                    scale,
+                    sleep_time,
+                    settings.interactive,
+                    settings.fit,
+                );
+                (
+                    FormatCollector::Pixels(collector),
+                    FormatWriter::Terminal(writer),
+                )
+            }
+            Format::Cast => {
+                let (collector, writer) = self::cast::create_cast(sleep_time);
+                (FormatCollector::Cast(collector), FormatWriter::Cast(writer))
+            }
+        })
     }
-    pub fn create_webp_renderer(scale: usize, sleep_time: f64) -> (impl Collector, impl Writer) {
-        self::webp::create_webp(scale, sleep_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::common::testing::{record, Gray};
+    use super::*;
+
+    #[derive(clap::Parser)]
+    struct Cli {
+        #[command(flatten)]
+        options: RenderOptions,
+    }
+
+    #[test]
+    fn validates_options() {
+        use clap::Parser;
+        let parse = |args: &[&str]| Cli::try_parse_from([&["render"], args].concat());
+
+        let options = parse(&["--scale", "1", "--fps", "0.5", "--hold-last", "0"])
+            .unwrap()
+            .options;
+        assert_eq!(options.scale, Some(1));
+        assert_eq!(options.frame_delay(), Some(2.0));
+        assert_eq!(options.hold_last, Some(0.0));
+
+        for args in [
+            ["--scale", "0"],
+            ["--fps", "0"],
+            ["--fps", "-1"],
+            ["--fps", "inf"],
+            ["--frame-delay", "0"],
+            ["--frame-delay", "-0.1"],
+            ["--hold-last", "-1"],
+            ["--hold-last", "NaN"],
+        ] {
+            assert!(parse(&args).is_err(), "{:?} should be rejected", args);
+        }
+    }
+
+    fn settings(format: Format, scale: usize) -> RecordingSettings {
+        RecordingSettings {
+            format,
+            scale,
+            hold_last: 0.1,
+            ..Default::default()
+        }
+    }
+
+    fn frame(rows: usize, cols: usize) -> Array2<Gray> {
+        Array2::from_shape_fn((rows, cols), |(row, col)| Gray((row * cols + col) as u8))
+    }
+
+    #[test]
+    fn frame_size_mismatch() {
+        for format in [Format::Gif, Format::Webp, Format::Apng] {
+            let (mut collector, _writer) =
+                Renderers::create_renderer(&settings(format, 2)).unwrap();
+
+            collector.add_frame(&frame(3, 4), 0.0).unwrap();
+            let err = collector.add_frame(&frame(4, 3), 0.1).unwrap_err();
+
+            assert!(
+                matches!(
+                    err,
+                    RenderError::FrameSize {
+                        frame: 1,
+                        expected: (8, 6),
+                        actual: (6, 8),
+                    }
+                ),
+                "{:?}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn writer_errors_reach_the_collector() {
+        for format in [Format::Gif, Format::Webp, Format::Apng] {
+            let (collector, writer) = Renderers::create_renderer(&settings(format, 1)).unwrap();
+            let path = Path::new("does/not/exist/animation");
+
+            let (written, collected) = record(collector, writer, path, None, |collector| {
+                (0..100).try_for_each(|i| collector.add_frame(&frame(2, 2), i as f64))
+            });
+
+            assert!(
+                matches!(written, Err(RenderError::Io { .. })),
+                "{:?}",
+                written
+            );
+            // The WebP writer only creates its file once all frames are encoded
+            if format == Format::Webp {
+                assert!(collected.is_ok(), "{:?}", collected);
+            } else {
+                assert!(
+                    matches!(collected, Err(RenderError::WriterStopped)),
+                    "{:?}",
+                    collected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn webp_without_frames() {
+        let path = std::env::temp_dir().join(format!("aoc_empty_{}.webp", std::process::id()));
+        let (collector, writer) = Renderers::create_renderer(&settings(Format::Webp, 1)).unwrap();
+
+        let (written, collected) = record(collector, writer, &path, Some(0), |_| Ok(()));
+
+        written.unwrap();
+        collected.unwrap();
+        assert!(!path.exists());
     }
 }
@@ -0,0 +1,344 @@
+use ndarray::{s, Array2};
+use rgb::RGBA8;
+
+/// The color of values that can't be mapped, like `NaN`.
+const MISSING: RGBA8 = RGBA8::new(0, 0, 0, 0);
+
+/// Colors that map values between 0 and 1 to colors, by interpolating between evenly spaced stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    /// Dark blue over green to yellow, perceptually uniform
+    Viridis,
+    /// Black over purple to light yellow, perceptually uniform
+    Magma,
+    /// Black to white
+    Grayscale,
+    /// Blue over light gray to red, for values around a meaningful center
+    Diverging,
+}
+
+const VIRIDIS: [[u8; 3]; 9] = [
+    [0x44, 0x01, 0x54],
+    [0x47, 0x2d, 0x7b],
+    [0x3b, 0x52, 0x8b],
+    [0x2c, 0x72, 0x8e],
+    [0x21, 0x91, 0x8c],
+    [0x28, 0xae, 0x80],
+    [0x5e, 0xc9, 0x62],
+    [0xad, 0xdc, 0x30],
+    [0xfd, 0xe7, 0x25],
+];
+
+const MAGMA: [[u8; 3]; 9] = [
+    [0x00, 0x00, 0x04],
+    [0x1c, 0x10, 0x44],
+    [0x4f, 0x12, 0x7b],
+    [0x81, 0x25, 0x81],
+    [0xb5, 0x36, 0x7a],
+    [0xe5, 0x50, 0x64],
+    [0xfb, 0x87, 0x61],
+    [0xfe, 0xc2, 0x87],
+    [0xfc, 0xfd, 0xbf],
+];
+
+const GRAYSCALE: [[u8; 3]; 2] = [[0x00, 0x00, 0x00], [0xff, 0xff, 0xff]];
+
+const DIVERGING: [[u8; 3]; 5] = [
+    [0x3b, 0x4c, 0xc0],
+    [0x8d, 0xb0, 0xfe],
+    [0xdd, 0xdd, 0xdd],
+    [0xf4, 0x9a, 0x7b],
+    [0xb4, 0x04, 0x26],
+];
+
+#[allow(dead_code)]
+impl Colormap {
+    fn stops(self) -> &'static [[u8; 3]] {
+        match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Grayscale => &GRAYSCALE,
+            Colormap::Diverging => &DIVERGING,
+        }
+    }
+
+    /// The color of a normalized value; values outside of 0 to 1 are clamped.
+    pub fn color(self, value: f64) -> RGBA8 {
+        if value.is_nan() {
+            return MISSING;
+        }
+        let stops = self.stops();
+        let position = value.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+        let index = (position.floor() as usize).min(stops.len() - 2);
+        let fraction = position - index as f64;
+
+        let [r, g, b] = [0, 1, 2].map(|channel| {
+            let (from, to) = (
+                f64::from(stops[index][channel]),
+                f64::from(stops[index + 1][channel]),
+            );
+            (from + (to - from) * fraction).round() as u8
+        });
+        RGBA8::new(r, g, b, 255)
+    }
+}
+
+/// How values are mapped to the range from 0 to 1 of a [`Colormap`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Normalization {
+    /// The smallest value becomes 0, the largest 1.
+    MinMax,
+    /// Like [`Normalization::MinMax`], but on a logarithmic scale, to tell apart small values
+    /// next to a few very large ones. Works for any values, since they are shifted to start at 1.
+    Log,
+    /// A fixed range, e.g. to keep the colors of an animation stable; values outside are clamped.
+    Fixed { min: f64, max: f64 },
+    /// A range symmetric around `center`, which becomes 0.5, e.g. for a [`Colormap::Diverging`].
+    /// It extends as far as the value furthest away from the center.
+    Centered { center: f64 },
+}
+
+/// The mapping of a [`Normalization`] for a concrete set of values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Range {
+    min: f64,
+    max: f64,
+    log: bool,
+}
+
+#[allow(dead_code)]
+impl Normalization {
+    fn range(self, values: impl Iterator<Item = f64>) -> Range {
+        let finite_range = || {
+            values
+                .filter(|value| value.is_finite())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                    (min.min(value), max.max(value))
+                })
+        };
+        let (min, max) = match self {
+            Normalization::Fixed { min, max } => (min, max),
+            Normalization::MinMax | Normalization::Log => finite_range(),
+            Normalization::Centered { center } => {
+                let (min, max) = finite_range();
+                // Without any values, both are infinite and the distance is NaN
+                let distance = (center - min).max(max - center).max(0.0);
+                (center - distance, center + distance)
+            }
+        };
+        Range {
+            min,
+            max,
+            log: self == Normalization::Log,
+        }
+    }
+
+    /// Normalizes all values, so that they can be passed to [`Colormap::color`].
+    pub fn apply(self, values: &[f64]) -> Vec<f64> {
+        let range = self.range(values.iter().copied());
+        values.iter().map(|&value| range.normalize(value)).collect()
+    }
+}
+
+impl Range {
+    fn normalize(&self, value: f64) -> f64 {
+        if value.is_nan() {
+            return f64::NAN;
+        }
+        // All values are equal, or there are none at all
+        if self.max <= self.min {
+            return 0.5;
+        }
+        let normalized = if self.log {
+            (1.0 + value - self.min).max(1.0).ln() / (1.0 + self.max - self.min).ln()
+        } else {
+            (value - self.min) / (self.max - self.min)
+        };
+        normalized.clamp(0.0, 1.0)
+    }
+}
+
+/// Numbers that can be displayed in a [`Heatmap`].
+pub trait Scalar: Copy {
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_scalar {
+    ($($type:ty),*) => {
+        $(
+            impl Scalar for $type {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// Turns arrays of numbers into frames, see [`Heatmap::render`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Heatmap {
+    colormap: Colormap,
+    normalization: Normalization,
+    /// The width of the legend bar, in cells.
+    legend: Option<usize>,
+}
+
+#[allow(dead_code)]
+impl Heatmap {
+    /// Creates a heatmap that spans the whole colormap, from the smallest value to the largest.
+    pub fn new(colormap: Colormap) -> Self {
+        Self {
+            colormap,
+            normalization: Normalization::MinMax,
+            legend: None,
+        }
+    }
+
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Adds a legend bar to the right of every frame, separated from it by an empty column.
+    /// It shows the colormap from the smallest value at the bottom to the largest at the top.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the bar, in cells.
+    pub fn with_legend(mut self, width: usize) -> Self {
+        self.legend = Some(width);
+        self
+    }
+
+    /// Maps every value to its color, so that the result can be added to a
+    /// [`super::Collector`] like any other frame.
+    pub fn render(&self, data: &Array2<impl Scalar>) -> Array2<RGBA8> {
+        let range = self
+            .normalization
+            .range(data.iter().map(|value| value.to_f64()));
+        let heatmap = data.map(|value| self.colormap.color(range.normalize(value.to_f64())));
+
+        match self.legend {
+            Some(width) if width > 0 => self.add_legend(heatmap, width),
+            _ => heatmap,
+        }
+    }
+
+    fn add_legend(&self, heatmap: Array2<RGBA8>, width: usize) -> Array2<RGBA8> {
+        let (rows, cols) = heatmap.dim();
+        let mut frame = Array2::from_elem((rows, cols + 1 + width), MISSING);
+        frame.slice_mut(s![.., ..cols]).assign(&heatmap);
+        for row in 0..rows {
+            let value = match rows {
+                1 => 0.5,
+                rows => 1.0 - row as f64 / (rows - 1) as f64,
+            };
+            frame
+                .slice_mut(s![row, cols + 1..])
+                .fill(self.colormap.color(value));
+        }
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_colors() {
+        assert_eq!(
+            Colormap::Viridis.color(0.0),
+            RGBA8::new(0x44, 0x01, 0x54, 255)
+        );
+        assert_eq!(
+            Colormap::Viridis.color(1.0),
+            RGBA8::new(0xfd, 0xe7, 0x25, 255)
+        );
+        assert_eq!(Colormap::Magma.color(-3.0), Colormap::Magma.color(0.0));
+        assert_eq!(
+            Colormap::Grayscale.color(0.5),
+            RGBA8::new(128, 128, 128, 255)
+        );
+        assert_eq!(
+            Colormap::Diverging.color(0.5),
+            RGBA8::new(0xdd, 0xdd, 0xdd, 255)
+        );
+        assert_eq!(Colormap::Grayscale.color(f64::NAN), MISSING);
+    }
+
+    #[test]
+    fn normalizes_values() {
+        let values = [2.0, 4.0, 10.0, f64::NAN];
+
+        let min_max = Normalization::MinMax.apply(&values);
+        assert_eq!(min_max[..3], [0.0, 0.25, 1.0]);
+        assert!(min_max[3].is_nan());
+
+        let log = Normalization::Log.apply(&values);
+        assert_eq!(log[0], 0.0);
+        assert!((log[1] - 3f64.ln() / 9f64.ln()).abs() < 1e-12);
+        assert_eq!(log[2], 1.0);
+
+        let fixed = Normalization::Fixed { min: 0.0, max: 5.0 }.apply(&values);
+        assert_eq!(fixed[..3], [0.4, 0.8, 1.0]);
+
+        assert_eq!(Normalization::MinMax.apply(&[3.0, 3.0]), [0.5, 0.5]);
+    }
+
+    #[test]
+    fn normalizes_around_center() {
+        let values = [-2.0, 0.0, 6.0, f64::NAN];
+
+        let centered = Normalization::Centered { center: 0.0 }.apply(&values);
+        assert!((centered[0] - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(centered[1..3], [0.5, 1.0]);
+        assert!(centered[3].is_nan());
+
+        let centered = Normalization::Centered { center: 2.0 }.apply(&values);
+        assert_eq!(centered[1..3], [0.25, 1.0]);
+        assert_eq!(Normalization::Centered { center: 1.0 }.apply(&[1.0]), [0.5]);
+
+        // The center gets the middle stop, even if all values are on one side of it
+        let distances = Array2::from_shape_vec((1, 3), vec![5i32, 7, 9]).unwrap();
+        let frame = Heatmap::new(Colormap::Diverging)
+            .with_normalization(Normalization::Centered { center: 5.0 })
+            .render(&distances);
+        assert_eq!(frame[(0, 0)], RGBA8::new(0xdd, 0xdd, 0xdd, 255));
+        assert_eq!(frame[(0, 2)], RGBA8::new(0xb4, 0x04, 0x26, 255));
+    }
+
+    #[test]
+    fn renders_integers() {
+        let heights = Array2::from_shape_vec((2, 2), vec![0u8, 9, 3, 6]).unwrap();
+
+        let frame = Heatmap::new(Colormap::Grayscale).render(&heights);
+
+        assert_eq!(
+            frame.map(|color| color.r),
+            Array2::from_shape_vec((2, 2), vec![0, 255, 85, 170]).unwrap()
+        );
+    }
+
+    #[test]
+    fn draws_legend() {
+        let distances = Array2::from_shape_vec((3, 2), vec![1i64, 2, 3, 4, 5, 6]).unwrap();
+
+        let frame = Heatmap::new(Colormap::Grayscale)
+            .with_normalization(Normalization::Fixed {
+                min: 0.0,
+                max: 10.0,
+            })
+            .with_legend(2)
+            .render(&distances);
+
+        assert_eq!(frame.dim(), (3, 5));
+        assert_eq!(frame[(0, 2)], MISSING);
+        assert_eq!(frame.column(3).map(|color| color.r).to_vec(), [255, 128, 0]);
+        assert_eq!(frame.column(3), frame.column(4));
+        assert_eq!(frame[(2, 1)].r, 153);
+    }
+}
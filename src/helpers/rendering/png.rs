@@ -0,0 +1,343 @@
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use imgref::ImgVec;
+use indicatif::ProgressBar;
+use rgb::{ComponentBytes, RGBA8};
+
+use super::{
+    common::{channel_collector, create_file, FrameReceiver, PixelCollector, Pixels},
+    RenderError,
+};
+
+/// The name of the file that lists the frames of a PNG sequence together with their durations,
+/// in the format of ffmpeg's concat demuxer.
+pub const FRAME_LIST: &str = "frames.ffconcat";
+
+/// Writes the frames as a single animated PNG.
+pub struct ApngWriter {
+    sleep_time: f64,
+    receiver: FrameReceiver<ImgVec<RGBA8>>,
+}
+
+/// Writes every frame as a numbered PNG into a directory.
+pub struct PngFramesWriter {
+    sleep_time: f64,
+    receiver: FrameReceiver<ImgVec<RGBA8>>,
+}
+
+pub fn create_apng(scale: usize, sleep_time: f64) -> (PixelCollector, ApngWriter) {
+    let (collector, receiver) = channel_collector(Pixels { scale });
+    (
+        collector,
+        ApngWriter {
+            sleep_time,
+            receiver,
+        },
+    )
+}
+
+pub fn create_png_frames(scale: usize, sleep_time: f64) -> (PixelCollector, PngFramesWriter) {
+    let (collector, receiver) = channel_collector(Pixels { scale });
+    (
+        collector,
+        PngFramesWriter {
+            sleep_time,
+            receiver,
+        },
+    )
+}
+
+fn png_encoder<W: Write>(writer: W, image: &ImgVec<RGBA8>) -> png::Encoder<'static, W> {
+    let mut encoder = png::Encoder::new(writer, image.width() as u32, image.height() as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+}
+
+/// Pairs every frame with the time until the next one; the last frame lasts `sleep_time`.
+fn with_durations(
+    frames: impl Iterator<Item = (ImgVec<RGBA8>, f64)>,
+    sleep_time: f64,
+) -> impl Iterator<Item = (ImgVec<RGBA8>, f64)> {
+    let mut frames = frames.peekable();
+    std::iter::from_fn(move || {
+        let (frame, timestamp) = frames.next()?;
+        let duration = frames.peek().map_or(sleep_time, |&(_, next_timestamp)| {
+            next_timestamp - timestamp
+        });
+        Some((frame, duration.max(0.0)))
+    })
+}
+
+/// The delay of an APNG frame as a fraction of seconds, as exact as its 16-bit parts allow.
+///
+/// Long delays get a coarser denominator instead of being cut off; delays of more than
+/// `u16::MAX` seconds are capped.
+fn frame_delay(duration: f64) -> (u16, u16) {
+    [1000, 100, 10, 1]
+        .into_iter()
+        .map(|denominator| ((duration * f64::from(denominator)).round(), denominator))
+        .find(|&(numerator, _)| numerator <= f64::from(u16::MAX))
+        .map_or((u16::MAX, 1), |(numerator, denominator)| {
+            (numerator as u16, denominator)
+        })
+}
+
+impl super::Writer for ApngWriter {
+    /// The number of frames is part of the header, so the frames are only streamed to the file
+    /// if `frame_count_hint` is given; it then has to be exact. Without it, all frames are
+    /// buffered until the collector is dropped.
+    fn write(
+        self,
+        filename: &impl AsRef<Path>,
+        frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError> {
+        let file = create_file(filename.as_ref())?;
+
+        let progress = ProgressBar::new(frame_count_hint.unwrap_or(0) as u64);
+        match frame_count_hint {
+            Some(num_frames) => write_apng(
+                file,
+                num_frames,
+                self.receiver.iter(),
+                self.sleep_time,
+                &progress,
+            )?,
+            None => {
+                let frames = self.receiver.iter().collect::<Vec<_>>();
+                progress.set_length(frames.len() as u64);
+                write_apng(
+                    file,
+                    frames.len(),
+                    frames.into_iter(),
+                    self.sleep_time,
+                    &progress,
+                )?
+            }
+        }
+        progress.finish();
+        Ok(())
+    }
+}
+
+/// Writes an animated PNG with exactly `num_frames` frames.
+///
+/// Fails with [`RenderError::FrameCount`] if there are more or fewer frames;
+/// superfluous frames are still received, so that the collector doesn't fail as well.
+fn write_apng(
+    file: File,
+    num_frames: usize,
+    frames: impl Iterator<Item = (ImgVec<RGBA8>, f64)>,
+    sleep_time: f64,
+    progress: &ProgressBar,
+) -> Result<(), RenderError> {
+    let mut frames = with_durations(frames, sleep_time).peekable();
+    let Some((first_frame, _)) = frames.peek() else {
+        return match num_frames {
+            0 => Ok(()),
+            expected => Err(RenderError::FrameCount {
+                expected,
+                actual: 0,
+            }),
+        };
+    };
+    if num_frames == 0 {
+        return Err(RenderError::FrameCount {
+            expected: 0,
+            actual: frames.count(),
+        });
+    }
+
+    let mut encoder = png_encoder(BufWriter::new(file), first_frame);
+    encoder.set_animated(num_frames as u32, 0)?;
+    let mut writer = encoder.write_header()?;
+
+    let mut written = 0;
+    while let Some((frame, duration)) = frames.next() {
+        if written == num_frames {
+            return Err(RenderError::FrameCount {
+                expected: num_frames,
+                actual: written + 1 + frames.count(),
+            });
+        }
+        let (numerator, denominator) = frame_delay(duration);
+        writer.set_frame_delay(numerator, denominator)?;
+        writer.write_image_data(frame.buf().as_bytes())?;
+        written += 1;
+        progress.inc(1);
+    }
+    if written < num_frames {
+        return Err(RenderError::FrameCount {
+            expected: num_frames,
+            actual: written,
+        });
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+impl super::Writer for PngFramesWriter {
+    /// Writes the frames as `frame_00000.png`, `frame_00001.png`, … into the directory `filename`,
+    /// which is created if necessary. Their durations are listed in [`FRAME_LIST`], e.g. for
+    /// `ffmpeg -f concat -i frames.ffconcat animation.mp4`.
+    fn write(
+        self,
+        filename: &impl AsRef<Path>,
+        frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError> {
+        let directory = filename.as_ref();
+        std::fs::create_dir_all(directory).map_err(|source| RenderError::Io {
+            path: directory.to_path_buf(),
+            source,
+        })?;
+
+        let progress = ProgressBar::new(frame_count_hint.unwrap_or(0) as u64);
+        let mut frame_list = String::from("ffconcat version 1.0\n");
+        let mut last_frame = None;
+        for (index, (frame, duration)) in
+            with_durations(self.receiver.iter(), self.sleep_time).enumerate()
+        {
+            let name = format!("frame_{:05}.png", index);
+            let path = directory.join(&name);
+            let file = create_file(&path)?;
+            let mut writer = png_encoder(BufWriter::new(file), &frame).write_header()?;
+            writer.write_image_data(frame.buf().as_bytes())?;
+            writer.finish()?;
+
+            let _ = writeln!(frame_list, "file '{}'\nduration {:.6}", name, duration);
+            last_frame = Some(name);
+            progress.inc(1);
+        }
+        // The concat demuxer ignores the duration of the last file unless it is repeated
+        if let Some(last_frame) = last_frame {
+            let _ = writeln!(frame_list, "file '{}'", last_frame);
+        }
+
+        let path = directory.join(FRAME_LIST);
+        std::fs::write(&path, frame_list).map_err(|source| RenderError::Io { path, source })?;
+        progress.finish();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::helpers::rendering::{
+        common::testing::{record, Gray},
+        Collector, Writer,
+    };
+
+    fn collect(
+        collector: PixelCollector,
+        writer: impl Writer + Send,
+        path: &Path,
+        frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError> {
+        let (written, collected) = record(collector, writer, path, frame_count_hint, |collector| {
+            for (frame, timestamp) in [0.0, 0.1, 0.3].into_iter().enumerate() {
+                let data = Array2::from_shape_fn((2, 3), |(row, col)| {
+                    Gray((frame * 100 + row * 3 + col) as u8)
+                });
+                collector.add_frame(&data, timestamp)?;
+            }
+            Ok(())
+        });
+        collected.unwrap();
+        written
+    }
+
+    #[test]
+    fn frame_delays() {
+        assert_eq!(frame_delay(0.1), (100, 1000));
+        assert_eq!(frame_delay(65.535), (65535, 1000));
+        assert_eq!(frame_delay(100.0), (10000, 100));
+        assert_eq!(frame_delay(3600.0), (36000, 10));
+        assert_eq!(frame_delay(20000.4), (20000, 1));
+        assert_eq!(frame_delay(1e9), (u16::MAX, 1));
+    }
+
+    #[test]
+    fn writes_apng() {
+        for frame_count_hint in [Some(3), None] {
+            let path = std::env::temp_dir().join(format!("aoc_apng_{}.png", std::process::id()));
+            let (collector, writer) = create_apng(2, 1.5);
+            collect(collector, writer, &path, frame_count_hint).unwrap();
+
+            let mut reader = png::Decoder::new(File::open(&path).unwrap())
+                .read_info()
+                .unwrap();
+            assert_eq!(reader.info().size(), (6, 4));
+            assert_eq!(reader.info().animation_control().unwrap().num_frames, 3);
+
+            let mut buffer = vec![0; reader.output_buffer_size()];
+            let mut delays = vec![];
+            for _ in 0..3 {
+                reader.next_frame(&mut buffer).unwrap();
+                let control = reader.info().frame_control().unwrap();
+                delays.push((control.delay_num, control.delay_den));
+            }
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!(delays, [(100, 1000), (200, 1000), (1500, 1000)]);
+            // The last frame, upscaled
+            assert_eq!(&buffer[..8], [200, 200, 200, 255, 200, 200, 200, 255]);
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_frame_counts() {
+        // The hint ends up in the header, so it has to match the actual number of frames
+        for frame_count_hint in [0, 2, 5] {
+            let path = std::env::temp_dir().join(format!(
+                "aoc_apng_{}_{}.png",
+                frame_count_hint,
+                std::process::id()
+            ));
+            let (collector, writer) = create_apng(2, 1.5);
+            let err = collect(collector, writer, &path, Some(frame_count_hint)).unwrap_err();
+            std::fs::remove_file(&path).unwrap();
+
+            assert!(
+                matches!(
+                    err,
+                    RenderError::FrameCount { expected, actual: 3 } if expected == frame_count_hint
+                ),
+                "{:?}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn writes_png_frames() {
+        let directory = std::env::temp_dir().join(format!("aoc_png_frames_{}", std::process::id()));
+        let (collector, writer) = create_png_frames(1, 1.5);
+        collect(collector, writer, &directory, None).unwrap();
+
+        let frame_list = std::fs::read_to_string(directory.join(FRAME_LIST)).unwrap();
+        let frame = png::Decoder::new(File::open(directory.join("frame_00002.png")).unwrap())
+            .read_info()
+            .unwrap()
+            .info()
+            .size();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(
+            frame_list,
+            "ffconcat version 1.0\n\
+             file 'frame_00000.png'\nduration 0.100000\n\
+             file 'frame_00001.png'\nduration 0.200000\n\
+             file 'frame_00002.png'\nduration 1.500000\n\
+             file 'frame_00002.png'\n"
+        );
+        assert_eq!(frame, (3, 2));
+    }
+}
@@ -0,0 +1,368 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use ndarray::Array2;
+
+use super::{
+    y4m::STDOUT_PATH, Collector, Fit, Format, FormatCollector, FramesCounter, RenderError,
+    Renderers, SvgOptions, ToChar, ToColor, Writer,
+};
+
+/// Everything needed to start a [`Recording`], see [`super::RenderOptions::settings`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RecordingSettings {
+    pub format: Format,
+    pub path: PathBuf,
+    /// The size of a single cell, in pixels.
+    pub scale: usize,
+    /// The time between two frames, in seconds.
+    pub frame_delay: f64,
+    /// How long the last frame stays visible, in seconds.
+    pub hold_last: f64,
+    /// Whether to play terminal renderings, instead of only printing the last frame.
+    pub interactive: bool,
+    /// How frames that don't fit into the terminal are displayed.
+    pub fit: Fit,
+    /// Decorations of SVG renderings.
+    pub svg: SvgOptions,
+}
+
+impl RecordingSettings {
+    /// Whether the rendering gets written to the standard output instead of a file.
+    pub fn writes_to_stdout(&self) -> bool {
+        self.format == Format::Y4m && self.path == Path::new(STDOUT_PATH)
+    }
+}
+
+/// The file produced by a [`Recording`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artifact {
+    /// The written file or directory, or `None` if the format doesn't write one.
+    pub path: Option<PathBuf>,
+    pub frames: usize,
+    /// The playback duration of the animation, in seconds.
+    pub duration: f64,
+    /// The size of the file, or of all files in the directory, in bytes,
+    /// or `None` if nothing was written to a file.
+    pub size: Option<u64>,
+    /// How long it took from starting the recording until the file was written.
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for Artifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(
+                f,
+                "{} ({} frames, {:.1} s",
+                path.display(),
+                self.frames,
+                self.duration
+            )?,
+            None => write!(
+                f,
+                "Playback ({} frames, {:.1} s",
+                self.frames, self.duration
+            )?,
+        }
+        if let Some(size) = self.size {
+            write!(f, ", {} KiB", size.div_ceil(1024))?;
+        }
+        write!(f, ", took {:.1} s)", self.elapsed.as_secs_f64())
+    }
+}
+
+enum Sink {
+    /// Only counts frames, to provide the encoder with a frame count hint for its progress bar.
+    Counting(FramesCounter),
+    Encoding {
+        collector: FormatCollector,
+        writer_thread: JoinHandle<Result<(), RenderError>>,
+    },
+}
+
+/// An animation that is being recorded to a file.
+///
+/// Owns the encoder, which runs on its own thread while frames are being added.
+pub struct Recording {
+    sink: Sink,
+    settings: RecordingSettings,
+    started: Instant,
+    last_timestamp: f64,
+}
+
+#[allow(dead_code)]
+impl Recording {
+    /// Starts the encoder for a new recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Where and how to record the animation.
+    /// * `frame_count_hint` - The expected number of frames, for the progress bar.
+    pub fn start(
+        settings: &RecordingSettings,
+        frame_count_hint: Option<usize>,
+    ) -> Result<Self, RenderError> {
+        let (collector, writer) = Renderers::create_renderer(settings)?;
+        let path = settings.path.clone();
+        let writer_thread = std::thread::spawn(move || writer.write(&path, frame_count_hint));
+
+        Ok(Self {
+            sink: Sink::Encoding {
+                collector,
+                writer_thread,
+            },
+            settings: settings.clone(),
+            started: Instant::now(),
+            last_timestamp: 0.0,
+        })
+    }
+
+    fn counting(settings: &RecordingSettings) -> Self {
+        Self {
+            sink: Sink::Counting(FramesCounter::new()),
+            settings: settings.clone(),
+            started: Instant::now(),
+            last_timestamp: 0.0,
+        }
+    }
+
+    /// Records an animation by calling `render` twice; the first pass only counts
+    /// the frames, so that the encoder knows the exact number of frames in advance.
+    /// Some formats need it for their header, others for a proper progress bar.
+    ///
+    /// `render` therefore has to be deterministic and add the same frames every time
+    /// it is called. If the second pass adds a different number of frames than the first,
+    /// the recording fails with [`RenderError::FrameCount`].
+    pub fn record(
+        settings: &RecordingSettings,
+        mut render: impl FnMut(&mut Recording) -> Result<(), RenderError>,
+    ) -> Result<Artifact, RenderError> {
+        let mut counting = Self::counting(settings);
+        render(&mut counting)?;
+        let started = counting.started;
+        let expected = counting.get_num_frames();
+
+        let mut recording = Self::start(settings, Some(expected))?;
+        recording.started = started;
+        let rendered = render(&mut recording);
+
+        // A failing writer makes the collector fail as well; its error is the more useful one
+        let artifact = recording.finish()?;
+        rendered?;
+        if artifact.frames != expected {
+            return Err(RenderError::FrameCount {
+                expected,
+                actual: artifact.frames,
+            });
+        }
+        Ok(artifact)
+    }
+
+    pub fn settings(&self) -> &RecordingSettings {
+        &self.settings
+    }
+
+    /// The time between two frames, in seconds.
+    pub fn frame_delay(&self) -> f64 {
+        self.settings.frame_delay
+    }
+
+    /// Waits for the encoder to write all frames to the file.
+    pub fn finish(self) -> Result<Artifact, RenderError> {
+        let frames = self.get_num_frames();
+        if let Sink::Encoding {
+            collector,
+            writer_thread,
+        } = self.sink
+        {
+            drop(collector);
+            writer_thread.join().expect("The writer thread panicked")?;
+        }
+
+        let size = (self.settings.format.writes_output() && !self.settings.writes_to_stdout())
+            .then(|| output_size(&self.settings.path));
+        let path = (self.settings.format.writes_output()).then_some(self.settings.path);
+
+        Ok(Artifact {
+            path,
+            frames,
+            duration: self.last_timestamp + self.settings.hold_last,
+            size,
+            elapsed: self.started.elapsed(),
+        })
+    }
+}
+
+/// The size of a file, or of all files directly inside a directory, in bytes.
+fn output_size(path: &Path) -> u64 {
+    match std::fs::read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok()?.metadata().ok())
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len())
+            .sum(),
+        Err(_) => std::fs::metadata(path).map_or(0, |metadata| metadata.len()),
+    }
+}
+
+impl Collector for Recording {
+    fn add_frame(
+        &mut self,
+        data: &Array2<impl ToColor>,
+        timestamp: f64,
+    ) -> Result<(), RenderError> {
+        match &mut self.sink {
+            Sink::Counting(counter) => counter.add_frame(data, timestamp)?,
+            Sink::Encoding { collector, .. } => collector.add_frame(data, timestamp)?,
+        }
+        self.last_timestamp = self.last_timestamp.max(timestamp);
+        Ok(())
+    }
+
+    fn add_text_frame(
+        &mut self,
+        data: &Array2<impl ToColor + ToChar>,
+        timestamp: f64,
+    ) -> Result<(), RenderError> {
+        match &mut self.sink {
+            Sink::Counting(counter) => counter.add_text_frame(data, timestamp)?,
+            Sink::Encoding { collector, .. } => collector.add_text_frame(data, timestamp)?,
+        }
+        self.last_timestamp = self.last_timestamp.max(timestamp);
+        Ok(())
+    }
+
+    fn get_num_frames(&self) -> usize {
+        match &self.sink {
+            Sink::Counting(counter) => counter.get_num_frames(),
+            Sink::Encoding { collector, .. } => collector.get_num_frames(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::common::testing::Gray;
+    use super::*;
+
+    fn settings(format: Format, name: &str) -> RecordingSettings {
+        RecordingSettings {
+            format,
+            path: std::env::temp_dir().join(format!(
+                "aoc_recording_{}_{}.{}",
+                std::process::id(),
+                name,
+                format.extension().unwrap()
+            )),
+            scale: 2,
+            frame_delay: 0.1,
+            hold_last: 0.5,
+            ..Default::default()
+        }
+    }
+
+    fn render(recording: &mut Recording) -> Result<(), RenderError> {
+        // More frames than fit into the channel of the WebP writer
+        for frame in 0..20 {
+            let timestamp = frame as f64 * recording.frame_delay();
+            recording.add_frame(&Array2::from_elem((3, 5), Gray(frame * 10)), timestamp)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn records_to_file() {
+        for format in [Format::Gif, Format::Webp, Format::Apng] {
+            let settings = settings(format, "records_to_file");
+            let mut passes = 0;
+
+            let artifact = Recording::record(&settings, |recording| {
+                passes += 1;
+                render(recording)
+            })
+            .unwrap();
+            std::fs::remove_file(&settings.path).unwrap();
+
+            assert_eq!(passes, 2);
+            assert_eq!(artifact.path.as_ref(), Some(&settings.path));
+            assert_eq!(artifact.frames, 20);
+            assert!((artifact.duration - 2.4).abs() < 1e-9);
+            assert!(artifact.size.unwrap() > 0);
+        }
+    }
+
+    #[test]
+    fn single_pass() {
+        let settings = settings(Format::Webp, "single_pass");
+
+        let mut recording = Recording::start(&settings, None).unwrap();
+        render(&mut recording).unwrap();
+        let artifact = recording.finish().unwrap();
+        std::fs::remove_file(&settings.path).unwrap();
+
+        assert_eq!(artifact.frames, 20);
+    }
+
+    #[test]
+    fn reports_writer_errors() {
+        let mut settings = settings(Format::Webp, "reports_writer_errors");
+        settings.path = Path::new("does/not/exist").join(settings.path.file_name().unwrap());
+
+        let err = Recording::record(&settings, render).unwrap_err();
+
+        assert!(matches!(err, RenderError::Io { .. }), "{:?}", err);
+    }
+
+    #[test]
+    fn rejects_nondeterministic_renders() {
+        let settings = settings(Format::Webp, "nondeterministic");
+        let mut frames = 2;
+
+        let err = Recording::record(&settings, |recording| {
+            frames += 1;
+            for frame in 0..frames {
+                let timestamp = frame as f64 * recording.frame_delay();
+                recording.add_frame(&Array2::from_elem((1, 1), Gray(0)), timestamp)?;
+            }
+            Ok(())
+        })
+        .unwrap_err();
+        std::fs::remove_file(&settings.path).unwrap();
+
+        assert!(
+            matches!(
+                err,
+                RenderError::FrameCount {
+                    expected: 3,
+                    actual: 4
+                }
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn stdout_has_no_size() {
+        let settings = RecordingSettings {
+            format: Format::Y4m,
+            path: PathBuf::from(STDOUT_PATH),
+            ..Default::default()
+        };
+        assert!(settings.writes_to_stdout());
+
+        let artifact = Artifact {
+            path: Some(settings.path),
+            frames: 3,
+            duration: 1.5,
+            size: None,
+            elapsed: Duration::ZERO,
+        };
+        assert_eq!(artifact.to_string(), "- (3 frames, 1.5 s, took 0.0 s)");
+    }
+}
@@ -0,0 +1,507 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fmt::Write as _,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use ndarray::Array2;
+use rgb::RGBA8;
+
+use super::{
+    common::{
+        blend_on_black, channel_collector, create_file, escape_xml, ChannelCollector,
+        FrameEncoding, FrameReceiver,
+    },
+    RenderError, ToChar, ToColor,
+};
+
+/// The width of grid lines, relative to the size of a cell.
+const GRID_LINE_WIDTH: f64 = 0.05;
+/// The height of labels, relative to the size of a cell.
+const LABEL_SIZE: f64 = 0.6;
+
+/// Optional decorations of SVG renderings.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SvgOptions {
+    /// Draw lines between the cells.
+    pub grid: bool,
+    /// Label cells with their character, for frames added with
+    /// [`super::Collector::add_text_frame`].
+    pub labels: bool,
+}
+
+/// The elements of a single frame, in cell coordinates.
+pub struct SvgFrame {
+    elements: String,
+    size: (usize, usize),
+}
+
+/// Encodes frames as SVG elements.
+pub struct SvgEncoding {
+    labels: bool,
+}
+
+impl FrameEncoding for SvgEncoding {
+    type Frame = SvgFrame;
+
+    fn encode(&self, data: &Array2<impl ToColor>) -> (SvgFrame, (usize, usize)) {
+        let (rows, cols) = data.dim();
+        let size = (cols, rows);
+        let elements = rects(data);
+        (SvgFrame { elements, size }, size)
+    }
+
+    fn encode_text(&self, data: &Array2<impl ToColor + ToChar>) -> (SvgFrame, (usize, usize)) {
+        let (frame, size) = self.encode(data);
+        let mut elements = frame.elements;
+        if self.labels {
+            elements.push_str(&labels(data));
+        }
+        (SvgFrame { elements, size }, size)
+    }
+}
+
+pub type SvgCollector = ChannelCollector<SvgEncoding>;
+
+/// Writes the frames as a single SVG, animated with SMIL if there is more than one.
+pub struct SvgWriter {
+    scale: usize,
+    sleep_time: f64,
+    grid: bool,
+    receiver: FrameReceiver<SvgFrame>,
+}
+
+/// Writes every frame as a numbered, static SVG into a directory.
+pub struct SvgFramesWriter {
+    scale: usize,
+    grid: bool,
+    receiver: FrameReceiver<SvgFrame>,
+}
+
+/// Creates a collector and writer for a single, possibly animated, SVG.
+///
+/// # Arguments
+///
+/// * `scale` - The size of a single cell when the SVG is displayed at its natural size.
+/// * `sleep_time` - How long the last frame stays visible, in seconds.
+/// * `options` - Which decorations to draw.
+pub fn create_svg(scale: usize, sleep_time: f64, options: SvgOptions) -> (SvgCollector, SvgWriter) {
+    let (collector, receiver) = channel_collector(SvgEncoding {
+        labels: options.labels,
+    });
+    (
+        collector,
+        SvgWriter {
+            scale,
+            sleep_time,
+            grid: options.grid,
+            receiver,
+        },
+    )
+}
+
+/// Creates a collector and writer for a directory of static SVGs, see [`create_svg`].
+pub fn create_svg_frames(scale: usize, options: SvgOptions) -> (SvgCollector, SvgFramesWriter) {
+    let (collector, receiver) = channel_collector(SvgEncoding {
+        labels: options.labels,
+    });
+    (
+        collector,
+        SvgFramesWriter {
+            scale,
+            grid: options.grid,
+            receiver,
+        },
+    )
+}
+
+fn fill(color: RGBA8) -> String {
+    let mut fill = format!("fill=\"#{:02x}{:02x}{:02x}\"", color.r, color.g, color.b);
+    if color.a < 255 {
+        let _ = write!(fill, " fill-opacity=\"{:.3}\"", f64::from(color.a) / 255.0);
+    }
+    fill
+}
+
+/// Draws the cells as rectangles; the most common color becomes the background,
+/// and horizontal runs of any other color are merged into a single rectangle.
+fn rects(data: &Array2<impl ToColor>) -> String {
+    let (rows, cols) = data.dim();
+    let colors = data.map(|cell| cell.to_color());
+
+    // Ties go to the color that comes first, so that the output doesn't depend on hashing
+    let mut counts = HashMap::new();
+    for (index, &color) in colors.iter().enumerate() {
+        counts.entry(color).or_insert((0, index)).0 += 1;
+    }
+    let background = counts
+        .into_iter()
+        .max_by_key(|&(_, (count, first))| (count, Reverse(first)));
+
+    let mut elements = String::new();
+    if let Some((background, _)) = background {
+        let _ = writeln!(
+            elements,
+            "<rect width=\"{}\" height=\"{}\" {}/>",
+            cols,
+            rows,
+            fill(background)
+        );
+    }
+    for (y, row) in colors.rows().into_iter().enumerate() {
+        let mut x = 0;
+        while x < cols {
+            let color = row[x];
+            let length = row
+                .iter()
+                .skip(x)
+                .take_while(|&&other| other == color)
+                .count();
+            if Some(color) != background.map(|(background, _)| background) {
+                let _ = writeln!(
+                    elements,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"1\" {}/>",
+                    x,
+                    y,
+                    length,
+                    fill(color)
+                );
+            }
+            x += length;
+        }
+    }
+    elements
+}
+
+/// Draws the character of every cell in its center, in black or white, whichever is more legible.
+fn labels(data: &Array2<impl ToColor + ToChar>) -> String {
+    let mut elements = String::new();
+    for ((y, x), cell) in data.indexed_iter() {
+        let label = cell.to_char();
+        if label.is_whitespace() {
+            continue;
+        }
+        let (r, g, b) = blend_on_black(cell.to_color());
+        let luma = 0.299 * f64::from(r) + 0.587 * f64::from(g) + 0.114 * f64::from(b);
+        let color = if luma > 128.0 { "#000000" } else { "#ffffff" };
+        let _ = writeln!(
+            elements,
+            "<text x=\"{}.5\" y=\"{}.5\" fill=\"{}\">{}</text>",
+            x,
+            y,
+            color,
+            escape_xml(&label.to_string())
+        );
+    }
+    elements
+}
+
+/// The opening tag of an SVG of the given size, in cells, together with the shared styles.
+fn svg_start((cols, rows): (usize, usize), scale: usize) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {cols} {rows}\" \
+         width=\"{width}\" height=\"{height}\" shape-rendering=\"crispEdges\">\n\
+         <style>text {{ font: {LABEL_SIZE}px monospace; text-anchor: middle; \
+         dominant-baseline: central; }}</style>\n",
+        width = cols * scale,
+        height = rows * scale,
+    )
+}
+
+/// Lines between all cells, drawn on top of every frame.
+fn grid_lines((cols, rows): (usize, usize)) -> String {
+    let mut path = String::new();
+    for x in 1..cols {
+        let _ = write!(path, "M{} 0V{}", x, rows);
+    }
+    for y in 1..rows {
+        let _ = write!(path, "M0 {}H{}", y, cols);
+    }
+    format!(
+        "<path d=\"{}\" stroke=\"#000000\" stroke-opacity=\"0.3\" stroke-width=\"{}\" fill=\"none\" \
+         shape-rendering=\"auto\"/>\n",
+        path, GRID_LINE_WIDTH
+    )
+}
+
+fn static_svg(frame: &SvgFrame, scale: usize, grid: bool) -> String {
+    let mut svg = svg_start(frame.size, scale);
+    svg.push_str(&frame.elements);
+    if grid {
+        svg.push_str(&grid_lines(frame.size));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The id of the animation that restarts the frames of an animated SVG.
+const LOOP_ID: &str = "loop";
+
+/// A frame of an animated SVG, shown from `offset` seconds into every loop for `duration` seconds.
+fn animated_frame(frame: &SvgFrame, offset: f64, duration: f64) -> String {
+    format!(
+        "<g display=\"none\">\n\
+         <set attributeName=\"display\" to=\"inline\" begin=\"{LOOP_ID}.begin+{:.6}s\" \
+         dur=\"{:.6}s\"/>\n\
+         {}</g>\n",
+        offset, duration, frame.elements
+    )
+}
+
+/// An invisible animation that lasts `duration` seconds and starts over once it ends, which
+/// starts the frames over as well.
+///
+/// The frames refer to it by its id, so it can come last, once the duration is known.
+fn animation_loop(duration: f64) -> String {
+    format!(
+        "<g><animate id=\"{LOOP_ID}\" attributeName=\"display\" values=\"inline\" \
+         begin=\"0;{LOOP_ID}.end\" dur=\"{:.6}s\"/></g>\n",
+        // SMIL doesn't allow animations without a duration
+        duration.max(1e-6)
+    )
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), RenderError> {
+    write_contents(create_file(path)?, path, contents)
+}
+
+fn write_contents(file: File, path: &Path, contents: &str) -> Result<(), RenderError> {
+    let io_error = |source| RenderError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let mut file = BufWriter::new(file);
+    file.write_all(contents.as_bytes()).map_err(io_error)?;
+    file.flush().map_err(io_error)
+}
+
+impl super::Writer for SvgWriter {
+    /// Writes every frame as soon as the next one arrives, which determines how long it's shown.
+    fn write(
+        self,
+        filename: &impl AsRef<Path>,
+        _frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError> {
+        let path = filename.as_ref();
+        let file = create_file(path)?;
+
+        let mut frames = self.receiver.iter();
+        let Some((first, start)) = frames.next() else {
+            // An empty image, rather than an empty file, which isn't a valid SVG
+            let svg = format!("{}</svg>\n", svg_start((0, 0), self.scale));
+            return write_contents(file, path, &svg);
+        };
+        let Some(second) = frames.next() else {
+            return write_contents(file, path, &static_svg(&first, self.scale, self.grid));
+        };
+
+        let io_error = |source| RenderError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let mut file = BufWriter::new(file);
+        let size = first.size;
+        file.write_all(svg_start(size, self.scale).as_bytes())
+            .map_err(io_error)?;
+
+        let (mut frame, mut timestamp) = (first, start);
+        for (next, next_timestamp) in std::iter::once(second).chain(frames) {
+            // Frames that would never be visible are left out
+            if next_timestamp > timestamp {
+                let group = animated_frame(&frame, timestamp - start, next_timestamp - timestamp);
+                file.write_all(group.as_bytes()).map_err(io_error)?;
+            }
+            (frame, timestamp) = (next, next_timestamp);
+        }
+        let mut svg = String::new();
+        if self.sleep_time > 0.0 {
+            svg.push_str(&animated_frame(&frame, timestamp - start, self.sleep_time));
+        }
+        svg.push_str(&animation_loop(timestamp - start + self.sleep_time));
+        if self.grid {
+            svg.push_str(&grid_lines(size));
+        }
+        svg.push_str("</svg>\n");
+        file.write_all(svg.as_bytes()).map_err(io_error)?;
+        file.flush().map_err(io_error)
+    }
+}
+
+impl super::Writer for SvgFramesWriter {
+    /// Writes the frames as `frame_00000.svg`, `frame_00001.svg`, … into the directory `filename`,
+    /// which is created if necessary.
+    fn write(
+        self,
+        filename: &impl AsRef<Path>,
+        _frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError> {
+        let directory = filename.as_ref();
+        std::fs::create_dir_all(directory).map_err(|source| RenderError::Io {
+            path: directory.to_path_buf(),
+            source,
+        })?;
+
+        for (index, (frame, _)) in self.receiver.iter().enumerate() {
+            let path = directory.join(format!("frame_{:05}.svg", index));
+            write_file(&path, &static_svg(&frame, self.scale, self.grid))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::rendering::{common::testing::record, Collector, Writer};
+
+    #[derive(Clone, Copy)]
+    struct Height(u8);
+
+    impl ToColor for Height {
+        fn to_color(&self) -> RGBA8 {
+            match self.0 {
+                0 => RGBA8::new(0, 0, 0, 255),
+                height => RGBA8::new(0, 25 * height, 0, 255),
+            }
+        }
+    }
+
+    impl ToChar for Height {
+        fn to_char(&self) -> char {
+            char::from(b'0' + self.0)
+        }
+    }
+
+    fn forest(heights: &[u8], cols: usize) -> Array2<Height> {
+        Array2::from_shape_vec(
+            (heights.len() / cols, cols),
+            heights.iter().map(|&height| Height(height)).collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn merges_runs() {
+        let data = forest(&[0, 0, 9, 9, 0, 9, 0, 0, 0], 3);
+
+        assert_eq!(
+            rects(&data),
+            "<rect width=\"3\" height=\"3\" fill=\"#000000\"/>\n\
+             <rect x=\"2\" y=\"0\" width=\"1\" height=\"1\" fill=\"#00e100\"/>\n\
+             <rect x=\"0\" y=\"1\" width=\"1\" height=\"1\" fill=\"#00e100\"/>\n\
+             <rect x=\"2\" y=\"1\" width=\"1\" height=\"1\" fill=\"#00e100\"/>\n"
+        );
+    }
+
+    #[test]
+    fn breaks_background_ties() {
+        // Hashing differs between maps, so a single run could pass by chance
+        for _ in 0..20 {
+            let background = rects(&forest(&[9, 0, 0, 9], 2));
+            assert!(
+                background.starts_with("<rect width=\"2\" height=\"2\" fill=\"#00e100\"/>\n"),
+                "{}",
+                background
+            );
+        }
+    }
+
+    #[test]
+    fn labels_cells() {
+        let data = forest(&[0, 9], 2);
+
+        assert_eq!(
+            labels(&data),
+            "<text x=\"0.5\" y=\"0.5\" fill=\"#ffffff\">0</text>\n\
+             <text x=\"1.5\" y=\"0.5\" fill=\"#000000\">9</text>\n"
+        );
+    }
+
+    fn record_forests(collector: SvgCollector, writer: impl Writer + Send, path: &Path) {
+        let (written, collected) = record(collector, writer, path, None, |collector| {
+            for (frame, timestamp) in [0.0, 1.0, 3.0].into_iter().enumerate() {
+                let heights = [frame as u8, 1, 2, 3];
+                collector.add_text_frame(&forest(&heights, 2), timestamp)?;
+            }
+            Ok(())
+        });
+        written.unwrap();
+        collected.unwrap();
+    }
+
+    #[test]
+    fn animates_frames() {
+        let path = std::env::temp_dir().join(format!("aoc_svg_{}.svg", std::process::id()));
+        let options = SvgOptions {
+            grid: true,
+            labels: true,
+        };
+        let (collector, writer) = create_svg(10, 1.0, options);
+        record_forests(collector, writer, &path);
+
+        let svg = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 2 2\" width=\"20\" height=\"20\""
+        ));
+        let timings = svg
+            .lines()
+            .filter_map(|line| line.strip_prefix("<set attributeName=\"display\" to=\"inline\" "))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            timings,
+            [
+                "begin=\"loop.begin+0.000000s\" dur=\"1.000000s\"/>",
+                "begin=\"loop.begin+1.000000s\" dur=\"2.000000s\"/>",
+                "begin=\"loop.begin+3.000000s\" dur=\"1.000000s\"/>",
+            ]
+        );
+        assert!(svg.contains(
+            "<animate id=\"loop\" attributeName=\"display\" values=\"inline\" \
+             begin=\"0;loop.end\" dur=\"4.000000s\"/>"
+        ));
+        assert!(svg.contains(">2</text>"));
+        assert!(svg.contains("<path d=\"M1 0V2M0 1H2\""));
+    }
+
+    #[test]
+    fn writes_empty_svg() {
+        let path = std::env::temp_dir().join(format!("aoc_empty_{}.svg", std::process::id()));
+        let (collector, writer) = create_svg(10, 1.0, SvgOptions::default());
+        drop(collector);
+        writer.write(&path, None).unwrap();
+
+        let svg = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 0 0\" width=\"0\" height=\"0\""
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn writes_static_frames() {
+        let directory = std::env::temp_dir().join(format!("aoc_svg_frames_{}", std::process::id()));
+        let (collector, writer) = create_svg_frames(10, SvgOptions::default());
+        record_forests(collector, writer, &directory);
+
+        let frames = (0..3)
+            .map(|index| {
+                std::fs::read_to_string(directory.join(format!("frame_{:05}.svg", index))).unwrap()
+            })
+            .collect::<Vec<_>>();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        for frame in frames {
+            assert!(!frame.contains("<animate"));
+            assert!(!frame.contains("<text"));
+            assert!(!frame.contains("<path"));
+            assert!(frame.ends_with("</svg>\n"));
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    time::{Duration, Instant},
+};
+
+use imgref::{ImgRef, ImgVec};
+use rgb::RGBA8;
+
+use super::{
+    common::{channel_collector, frame_to_ansi, FrameReceiver, PixelCollector, Pixels},
+    RenderError,
+};
+
+/// How frames that are larger than the terminal get displayed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Fit {
+    /// Shrink the frame until it fits
+    #[default]
+    Scale,
+    /// Only show the center of the frame
+    Crop,
+}
+
+/// The terminal size to assume if it can't be determined.
+const FALLBACK_SIZE: (usize, usize) = (80, 24);
+
+/// Draws the frames in the terminal, at the pace given by their timestamps.
+pub struct TerminalWriter {
+    scale: usize,
+    sleep_time: f64,
+    interactive: bool,
+    fit: Fit,
+    receiver: FrameReceiver<ImgVec<RGBA8>>,
+}
+
+/// Creates a collector and writer for terminal playback.
+///
+/// # Arguments
+///
+/// * `scale` - The preferred size of a single cell, in pixels. Gets reduced if the frame
+///   wouldn't fit into the terminal otherwise.
+/// * `sleep_time` - How long the last frame stays visible, in seconds.
+/// * `interactive` - Whether to play the animation; otherwise only the last frame gets printed.
+///   Playback requires the standard output to be a terminal.
+/// * `fit` - How frames that are larger than the terminal get displayed.
+pub fn create_terminal(
+    scale: usize,
+    sleep_time: f64,
+    interactive: bool,
+    fit: Fit,
+) -> (PixelCollector, TerminalWriter) {
+    let (collector, receiver) = channel_collector(Pixels { scale: 1 });
+    (
+        collector,
+        TerminalWriter {
+            scale,
+            sleep_time,
+            interactive: interactive && io::stdout().is_terminal(),
+            fit,
+            receiver,
+        },
+    )
+}
+
+impl super::Writer for TerminalWriter {
+    fn write(
+        self,
+        _filename: &impl AsRef<std::path::Path>,
+        _frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError> {
+        let result = if self.interactive {
+            self.play()
+        } else {
+            self.print_last()
+        };
+        result.map_err(RenderError::Terminal)
+    }
+}
+
+impl TerminalWriter {
+    fn print_last(self) -> io::Result<()> {
+        if let Some((frame, _)) = self.receiver.iter().last() {
+            // Output that is redirected, e.g. to a log, has no size to fit the frame into
+            let frame = match terminal_size() {
+                Some(size) => fit_to(frame.as_ref(), self.scale, size, self.fit),
+                None => frame,
+            };
+            io::stdout()
+                .lock()
+                .write_all(frame_to_ansi(frame).as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn play(self) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+
+        let _cursor = HiddenCursor::hide(&mut stdout)?;
+        self.play_frames(&mut stdout)
+    }
+
+    fn play_frames(self, stdout: &mut impl Write) -> io::Result<()> {
+        let started = Instant::now();
+        let mut first_timestamp = None;
+        let mut drawn_lines = 0;
+
+        for (frame, timestamp) in self.receiver.iter() {
+            let first_timestamp = *first_timestamp.get_or_insert(timestamp);
+            let due = started + Duration::from_secs_f64((timestamp - first_timestamp).max(0.0));
+            std::thread::sleep(due.saturating_duration_since(Instant::now()));
+
+            // Leave one line for the cursor, so the terminal doesn't scroll
+            let (cols, rows) = terminal_size().unwrap_or(FALLBACK_SIZE);
+            let size = (cols, rows.saturating_sub(1).max(1));
+
+            let text = frame_to_ansi(fit_to(frame.as_ref(), self.scale, size, self.fit));
+            if drawn_lines > 0 {
+                write!(stdout, "\x1b[{}A", drawn_lines)?;
+            }
+            // Clear whatever is left of a previous, larger frame
+            write!(stdout, "{}\x1b[J", text)?;
+            stdout.flush()?;
+            drawn_lines = text.lines().count();
+        }
+        std::thread::sleep(Duration::from_secs_f64(self.sleep_time.max(0.0)));
+        Ok(())
+    }
+}
+
+const HIDE_CURSOR: &[u8] = b"\x1b[?25l";
+const SHOW_CURSOR: &[u8] = b"\x1b[?25h";
+
+/// Keeps the cursor hidden during playback.
+///
+/// The cursor is shown again when the guard is dropped, so also after an error or a panic.
+/// Ctrl-C ends the process without dropping anything, so it gets handled separately.
+struct HiddenCursor;
+
+impl HiddenCursor {
+    fn hide(stdout: &mut impl Write) -> io::Result<Self> {
+        #[cfg(unix)]
+        // SAFETY: The handler only calls async-signal-safe functions
+        unsafe {
+            libc::signal(
+                libc::SIGINT,
+                show_cursor_and_exit as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+        stdout.write_all(HIDE_CURSOR)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for HiddenCursor {
+    fn drop(&mut self) {
+        // Standard output is locked by the same thread during playback, which doesn't block
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(SHOW_CURSOR);
+        let _ = stdout.flush();
+        #[cfg(unix)]
+        // SAFETY: Restores the default handler
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+        }
+    }
+}
+
+/// Shows the cursor, then lets the signal end the process as usual.
+#[cfg(unix)]
+extern "C" fn show_cursor_and_exit(signal: libc::c_int) {
+    // SAFETY: `write`, `signal` and `raise` are async-signal-safe
+    unsafe {
+        libc::write(
+            libc::STDOUT_FILENO,
+            SHOW_CURSOR.as_ptr().cast(),
+            SHOW_CURSOR.len(),
+        );
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+/// The size of the terminal, in characters.
+fn terminal_size() -> Option<(usize, usize)> {
+    let (terminal_size::Width(cols), terminal_size::Height(rows)) = terminal_size::terminal_size()?;
+    Some((cols.into(), rows.into()))
+}
+
+/// Scales or crops an image so that it fits into a terminal of the given size.
+///
+/// Every character holds two vertically stacked pixels.
+fn fit_to(
+    image: ImgRef<RGBA8>,
+    scale: usize,
+    (cols, rows): (usize, usize),
+    fit: Fit,
+) -> ImgVec<RGBA8> {
+    let (max_width, max_height) = (cols.max(1), rows.max(1).saturating_mul(2));
+    let (width, height) = (image.width(), image.height());
+
+    if width == 0 || height == 0 {
+        return ImgVec::new(vec![], width, height);
+    }
+
+    if width <= max_width && height <= max_height {
+        let scale = scale.min(max_width / width).min(max_height / height).max(1);
+        return ImgVec::new(
+            (0..height * scale)
+                .flat_map(|y| (0..width * scale).map(move |x| image[(x / scale, y / scale)]))
+                .collect(),
+            width * scale,
+            height * scale,
+        );
+    }
+
+    match fit {
+        Fit::Scale => {
+            let factor = width.div_ceil(max_width).max(height.div_ceil(max_height));
+            let (new_width, new_height) = (width.div_ceil(factor), height.div_ceil(factor));
+            let pixels = (0..new_height)
+                .flat_map(|y| (0..new_width).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    average(
+                        (y * factor..((y + 1) * factor).min(height)).flat_map(|src_y| {
+                            (x * factor..((x + 1) * factor).min(width))
+                                .map(move |src_x| image[(src_x, src_y)])
+                        }),
+                    )
+                })
+                .collect();
+            ImgVec::new(pixels, new_width, new_height)
+        }
+        Fit::Crop => {
+            let (new_width, new_height) = (width.min(max_width), height.min(max_height));
+            let (left, top) = ((width - new_width) / 2, (height - new_height) / 2);
+            let pixels = image
+                .sub_image(left, top, new_width, new_height)
+                .pixels()
+                .collect();
+            ImgVec::new(pixels, new_width, new_height)
+        }
+    }
+}
+
+fn average(pixels: impl Iterator<Item = RGBA8>) -> RGBA8 {
+    let (mut sum, mut count) = ([0u32; 4], 0);
+    for pixel in pixels {
+        for (sum, value) in sum.iter_mut().zip([pixel.r, pixel.g, pixel.b, pixel.a]) {
+            *sum += u32::from(value);
+        }
+        count += 1;
+    }
+    let [r, g, b, a] = sum.map(|sum| (sum / count.max(1)) as u8);
+    RGBA8::new(r, g, b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: usize, height: usize) -> ImgVec<RGBA8> {
+        ImgVec::new(
+            (0..width * height)
+                .map(|i| RGBA8::new(i as u8, 0, 0, 255))
+                .collect(),
+            width,
+            height,
+        )
+    }
+
+    fn red(image: &ImgVec<RGBA8>) -> Vec<u8> {
+        image.pixels().map(|pixel| pixel.r).collect()
+    }
+
+    #[test]
+    fn scales_up_to_the_preferred_scale() {
+        let scaled = fit_to(image(2, 1).as_ref(), 3, (80, 24), Fit::Scale);
+        assert_eq!((scaled.width(), scaled.height()), (6, 3));
+        assert_eq!(red(&scaled), [0, 0, 0, 1, 1, 1].repeat(3));
+
+        // Limited by the two pixel rows of a single line
+        let scaled = fit_to(image(2, 1).as_ref(), 3, (80, 1), Fit::Scale);
+        assert_eq!((scaled.width(), scaled.height()), (4, 2));
+    }
+
+    #[test]
+    fn empty_images() {
+        let fitted = fit_to(image(3, 0).as_ref(), 2, (80, 24), Fit::Scale);
+        assert_eq!((fitted.width(), fitted.height()), (3, 0));
+    }
+
+    #[test]
+    fn shrinks_large_images() {
+        let shrunk = fit_to(image(4, 4).as_ref(), 1, (2, 1), Fit::Scale);
+        assert_eq!((shrunk.width(), shrunk.height()), (2, 2));
+        assert_eq!(red(&shrunk), [2, 4, 10, 12]);
+
+        let cropped = fit_to(image(4, 4).as_ref(), 1, (2, 1), Fit::Crop);
+        assert_eq!(red(&cropped), [5, 6, 9, 10]);
+    }
+
+    #[test]
+    fn half_blocks() {
+        let image = ImgVec::new(
+            vec![
+                RGBA8::new(255, 0, 0, 255),
+                RGBA8::new(0, 0, 255, 255),
+                RGBA8::new(0, 255, 0, 128),
+                RGBA8::new(0, 0, 0, 0),
+                RGBA8::new(1, 2, 3, 255),
+                RGBA8::new(4, 5, 6, 255),
+            ],
+            2,
+            3,
+        );
+
+        assert_eq!(
+            frame_to_ansi(image),
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;128;0m▀\x1b[38;2;0;0;255m\x1b[48;2;0;0;0m▀\x1b[0m\n\
+             \x1b[38;2;1;2;3m\x1b[49m▀\x1b[38;2;4;5;6m\x1b[49m▀\x1b[0m\n"
+        );
+    }
+}
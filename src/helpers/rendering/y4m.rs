@@ -0,0 +1,389 @@
+use std::{
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use imgref::ImgVec;
+use indicatif::ProgressBar;
+use rgb::RGBA8;
+
+use super::{
+    common::{
+        blend_on_black, channel_collector, create_file, FrameReceiver, PixelCollector, Pixels,
+    },
+    RenderError,
+};
+use crate::helpers::math::gcd;
+
+/// The output path that makes the writer use the standard output, e.g. to pipe into ffmpeg.
+pub const STDOUT_PATH: &str = "-";
+
+/// Writes the frames as an uncompressed YUV4MPEG2 video stream.
+///
+/// The frame rate is derived from the timestamps of the frames: it is the rate of the shortest
+/// delay between the first [`RATE_WINDOW`] frames, which are buffered until the rate is known.
+/// The timestamps are then resampled to that rate: every frame is shown for as many video frames
+/// as fit into its duration, and later frames that are too short to fill a single video frame
+/// get dropped.
+pub struct Y4mWriter {
+    sleep_time: f64,
+    receiver: FrameReceiver<ImgVec<RGBA8>>,
+}
+
+pub fn create_y4m(scale: usize, sleep_time: f64) -> (PixelCollector, Y4mWriter) {
+    let (collector, receiver) = channel_collector(Pixels { scale });
+    (
+        collector,
+        Y4mWriter {
+            sleep_time,
+            receiver,
+        },
+    )
+}
+
+/// The number of frames whose timestamps determine the frame rate of the video.
+const RATE_WINDOW: usize = 16;
+
+/// The shortest positive delay between consecutive timestamps.
+fn shortest_delay(timestamps: impl Iterator<Item = f64>) -> Option<f64> {
+    let timestamps = timestamps.collect::<Vec<_>>();
+    timestamps
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        // Ignore rounding errors of timestamps that are meant to be equal
+        .filter(|&delay| delay > 1e-6)
+        .min_by(f64::total_cmp)
+}
+
+/// The frame rate as a fraction with the smallest denominator that represents it exactly,
+/// e.g. 100:3 for a delay of 0.03 seconds, or with millihertz precision if there is none.
+fn frame_rate(frame_delay: f64) -> (u64, u64) {
+    let rate = 1.0 / frame_delay;
+    let fraction = |denominator: u64| {
+        let numerator = ((rate * denominator as f64).round() as u64).max(1);
+        (numerator, denominator)
+    };
+
+    (1..1000)
+        .map(fraction)
+        .find(|&(numerator, denominator)| {
+            (numerator as f64 / denominator as f64 - rate).abs() < rate * 1e-6
+        })
+        .unwrap_or_else(|| {
+            let (numerator, denominator) = fraction(1000);
+            let divisor = gcd(numerator, denominator);
+            (numerator / divisor, denominator / divisor)
+        })
+}
+
+/// Converts an image to planar YUV 4:2:0 with BT.601 coefficients in limited range.
+///
+/// Transparent pixels are blended on black, each chroma sample is the average of 2x2 pixels.
+fn rgba_to_yuv420(image: &ImgVec<RGBA8>) -> Vec<u8> {
+    let (width, height) = (image.width(), image.height());
+    let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+    let rgb = image
+        .pixels()
+        .map(|pixel| {
+            let (r, g, b) = blend_on_black(pixel);
+            [f64::from(r), f64::from(g), f64::from(b)]
+        })
+        .collect::<Vec<_>>();
+
+    let mut yuv = Vec::with_capacity(width * height + 2 * chroma_width * chroma_height);
+    yuv.extend(
+        rgb.iter().map(|[r, g, b]| {
+            (16.0 + (65.481 * r + 128.553 * g + 24.966 * b) / 255.0).round() as u8
+        }),
+    );
+
+    let chroma = |weights: [f64; 3]| {
+        (0..chroma_height)
+            .flat_map(|y| (0..chroma_width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let block = (2 * y..(2 * y + 2).min(height))
+                    .flat_map(|y| (2 * x..(2 * x + 2).min(width)).map(move |x| y * width + x))
+                    .map(|index| {
+                        let [r, g, b] = rgb[index];
+                        weights[0] * r + weights[1] * g + weights[2] * b
+                    })
+                    .collect::<Vec<_>>();
+                let average = block.iter().sum::<f64>() / block.len() as f64;
+                (128.0 + average / 255.0).round() as u8
+            })
+            .collect::<Vec<_>>()
+    };
+    yuv.extend(chroma([-37.797, -74.203, 112.0]));
+    yuv.extend(chroma([112.0, -93.786, -18.214]));
+    yuv
+}
+
+/// Writes video frames at a constant rate, repeating each animation frame until the next one is due.
+struct Resampler<W: Write> {
+    output: W,
+    /// Only known once the first frames have arrived, see [`RATE_WINDOW`].
+    frame_rate: Option<(u64, u64)>,
+    /// The frames that arrived before the frame rate was known.
+    buffered: Vec<(ImgVec<RGBA8>, f64)>,
+    /// The timestamp of the first frame, which becomes the start of the video.
+    start: f64,
+    /// The frame that is shown until the next one is due, with its timestamp.
+    pending: Option<(ImgVec<RGBA8>, f64)>,
+    written_frames: u64,
+}
+
+impl<W: Write> Resampler<W> {
+    fn new(output: W) -> Self {
+        Self {
+            output,
+            frame_rate: None,
+            buffered: vec![],
+            start: 0.0,
+            pending: None,
+            written_frames: 0,
+        }
+    }
+
+    fn add(&mut self, frame: ImgVec<RGBA8>, timestamp: f64) -> io::Result<()> {
+        if self.frame_rate.is_some() {
+            return self.show(frame, timestamp);
+        }
+        self.buffered.push((frame, timestamp));
+        if self.buffered.len() == RATE_WINDOW {
+            let delay = shortest_delay(self.buffered.iter().map(|&(_, timestamp)| timestamp));
+            self.start_video(delay)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the frame rate to the given delay between frames, and writes the buffered frames.
+    ///
+    /// Without any delay, there is only a single image to show, so the rate doesn't matter.
+    fn start_video(&mut self, delay: Option<f64>) -> io::Result<()> {
+        self.frame_rate = Some(frame_rate(delay.unwrap_or(1.0)));
+        for (frame, timestamp) in std::mem::take(&mut self.buffered) {
+            self.show(frame, timestamp)?;
+        }
+        Ok(())
+    }
+
+    fn show(&mut self, frame: ImgVec<RGBA8>, timestamp: f64) -> io::Result<()> {
+        match self.pending.take() {
+            None => {
+                self.start = timestamp;
+                self.write_header(&frame)?;
+            }
+            Some((pending, _)) => self.write_until(&pending, timestamp)?,
+        }
+        self.pending = Some((frame, timestamp));
+        Ok(())
+    }
+
+    fn finish(mut self, sleep_time: f64) -> io::Result<()> {
+        if self.frame_rate.is_none() {
+            // Too few frames for the window; the last one lasts as long as it is held
+            let end = self
+                .buffered
+                .last()
+                .map(|&(_, timestamp)| timestamp + sleep_time);
+            let timestamps = self.buffered.iter().map(|&(_, timestamp)| timestamp);
+            self.start_video(shortest_delay(timestamps.chain(end)))?;
+        }
+        if let Some((pending, timestamp)) = self.pending.take() {
+            self.write_until(&pending, timestamp + sleep_time)?;
+            // Even a single, very short frame should end up in the video
+            if self.written_frames == 0 {
+                self.write_frame(&pending)?;
+            }
+        }
+        self.output.flush()
+    }
+
+    fn write_header(&mut self, frame: &ImgVec<RGBA8>) -> io::Result<()> {
+        let (numerator, denominator) = self.frame_rate();
+        // The color range has to match the conversion in `rgba_to_yuv420`
+        writeln!(
+            self.output,
+            "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C420 XCOLORRANGE=LIMITED",
+            frame.width(),
+            frame.height(),
+            numerator,
+            denominator
+        )
+    }
+
+    fn frame_rate(&self) -> (u64, u64) {
+        self.frame_rate
+            .expect("The frame rate is known before the first frame is shown")
+    }
+
+    /// Repeats the frame for all video frames that start before `timestamp`.
+    fn write_until(&mut self, frame: &ImgVec<RGBA8>, timestamp: f64) -> io::Result<()> {
+        let (numerator, denominator) = self.frame_rate();
+        let frame_start = |frame: u64| (frame * denominator) as f64 / numerator as f64;
+        let mut data = None;
+        // Allow for rounding errors, so that frames aren't duplicated by accident
+        while frame_start(self.written_frames) < timestamp - self.start - 1e-6 {
+            let data = data.get_or_insert_with(|| rgba_to_yuv420(frame));
+            self.output.write_all(b"FRAME\n")?;
+            self.output.write_all(data)?;
+            self.written_frames += 1;
+        }
+        Ok(())
+    }
+
+    fn write_frame(&mut self, frame: &ImgVec<RGBA8>) -> io::Result<()> {
+        self.output.write_all(b"FRAME\n")?;
+        self.output.write_all(&rgba_to_yuv420(frame))?;
+        self.written_frames += 1;
+        Ok(())
+    }
+}
+
+impl super::Writer for Y4mWriter {
+    /// Writes to the standard output if `filename` is [`STDOUT_PATH`].
+    fn write(
+        self,
+        filename: &impl AsRef<Path>,
+        frame_count_hint: Option<usize>,
+    ) -> Result<(), RenderError> {
+        let path = filename.as_ref();
+        let io_error = |source| RenderError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let output: Box<dyn Write> = if path == Path::new(STDOUT_PATH) {
+            Box::new(BufWriter::new(io::stdout().lock()))
+        } else {
+            Box::new(BufWriter::new(create_file(path)?))
+        };
+
+        let progress = ProgressBar::new(frame_count_hint.unwrap_or(0) as u64);
+        let mut resampler = Resampler::new(output);
+        for (frame, timestamp) in self.receiver.iter() {
+            resampler.add(frame, timestamp).map_err(io_error)?;
+            progress.inc(1);
+        }
+        resampler.finish(self.sleep_time).map_err(io_error)?;
+        progress.finish();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(pixels: Vec<RGBA8>, width: usize, height: usize) -> ImgVec<RGBA8> {
+        ImgVec::new(pixels, width, height)
+    }
+
+    fn gray(value: u8) -> ImgVec<RGBA8> {
+        image(vec![RGBA8::new(value, value, value, 255); 4], 2, 2)
+    }
+
+    #[test]
+    fn converts_to_yuv420() {
+        let white = RGBA8::new(255, 255, 255, 255);
+        let red = RGBA8::new(255, 0, 0, 255);
+        let transparent = RGBA8::new(255, 255, 255, 0);
+
+        assert_eq!(
+            rgba_to_yuv420(&image(vec![white, transparent, red], 3, 1)),
+            [235, 16, 81, 128, 90, 128, 240]
+        );
+    }
+
+    #[test]
+    fn frame_rates() {
+        assert_eq!(frame_rate(0.04), (25, 1));
+        assert_eq!(frame_rate(0.03), (100, 3));
+        assert_eq!(frame_rate(1.5), (2, 3));
+    }
+
+    /// The luma of the top left pixel of every frame, for 2x2 frames.
+    fn lumas(frames: &[u8]) -> Vec<u8> {
+        // Six bytes per frame, of which the first is the luma of the top left pixel
+        frames
+            .chunks(b"FRAME\n".len() + 6)
+            .map(|frame| frame[b"FRAME\n".len()])
+            .collect()
+    }
+
+    /// Resamples 2x2 frames of the given gray values and timestamps.
+    ///
+    /// # Returns
+    ///
+    /// The header and the luma of the top left pixel of every video frame.
+    fn resample(frames: &[(u8, f64)], sleep_time: f64) -> (String, Vec<u8>) {
+        let mut output = vec![];
+        let mut resampler = Resampler::new(&mut output);
+        for &(value, timestamp) in frames {
+            resampler.add(gray(value), timestamp).unwrap();
+        }
+        resampler.finish(sleep_time).unwrap();
+
+        let (header, frames) =
+            output.split_at(output.iter().position(|&b| b == b'\n').unwrap() + 1);
+        (String::from_utf8(header.to_vec()).unwrap(), lumas(frames))
+    }
+
+    /// The luma of the given gray value, repeated for the given number of video frames.
+    fn repeated(frames: &[(u8, usize)]) -> Vec<u8> {
+        frames
+            .iter()
+            .flat_map(|&(value, count)| vec![rgba_to_yuv420(&gray(value))[0]; count])
+            .collect()
+    }
+
+    #[test]
+    fn derives_frame_rate() {
+        let (header, lumas) = resample(&[(0, 1.0), (1, 1.5), (2, 1.75), (3, 2.5)], 0.25);
+
+        assert_eq!(
+            header,
+            "YUV4MPEG2 W2 H2 F4:1 Ip A1:1 C420 XCOLORRANGE=LIMITED\n"
+        );
+        assert_eq!(lumas, repeated(&[(0, 2), (1, 1), (2, 3), (3, 1)]));
+    }
+
+    #[test]
+    fn rate_from_hold_time() {
+        let (header, lumas) = resample(&[(0, 0.0)], 0.5);
+
+        assert_eq!(
+            header,
+            "YUV4MPEG2 W2 H2 F2:1 Ip A1:1 C420 XCOLORRANGE=LIMITED\n"
+        );
+        assert_eq!(lumas, repeated(&[(0, 1)]));
+    }
+
+    #[test]
+    fn drops_frames_after_window() {
+        // The rate is fixed by the first frames, so a later, shorter frame doesn't fit in
+        let mut frames = (0..RATE_WINDOW)
+            .map(|frame| (0, frame as f64 * 0.1))
+            .collect::<Vec<_>>();
+        frames.extend([(1, 1.6), (2, 1.62), (3, 1.7)]);
+
+        let (header, lumas) = resample(&frames, 0.2);
+
+        assert_eq!(
+            header,
+            "YUV4MPEG2 W2 H2 F10:1 Ip A1:1 C420 XCOLORRANGE=LIMITED\n"
+        );
+        assert_eq!(lumas, repeated(&[(0, RATE_WINDOW), (1, 1), (3, 2)]));
+    }
+
+    #[test]
+    fn single_frame() {
+        let mut output = vec![];
+        let mut resampler = Resampler::new(&mut output);
+        resampler.add(gray(0), 0.0).unwrap();
+        resampler.finish(0.0).unwrap();
+
+        let header = b"YUV4MPEG2 W2 H2 F1:1 Ip A1:1 C420 XCOLORRANGE=LIMITED\n";
+        assert!(output.starts_with(header));
+        assert_eq!(output.len(), header.len() + 6 + 6);
+    }
+}
@@ -0,0 +1,358 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// The result of a successful path search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N, C> {
+    /// The total cost of the path.
+    pub cost: C,
+    /// All nodes of the path, from the start node to the goal node.
+    pub nodes: Vec<N>,
+}
+
+/// Keeps track of all discovered nodes and where they were reached from.
+struct Discovered<N, C> {
+    nodes: Vec<(N, Option<usize>, C)>,
+    ids: HashMap<N, usize>,
+}
+
+impl<N, C> Discovered<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord,
+{
+    fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    /// Records a node, if it is new or was reached more cheaply than before.
+    fn discover(&mut self, node: N, parent: Option<usize>, cost: C) -> Option<usize> {
+        match self.ids.entry(node) {
+            Entry::Occupied(entry) => {
+                let id = *entry.get();
+                let known = &mut self.nodes[id];
+                (cost < known.2).then(|| {
+                    known.1 = parent;
+                    known.2 = cost;
+                    id
+                })
+            }
+            Entry::Vacant(entry) => {
+                let id = self.nodes.len();
+                self.nodes.push((entry.key().clone(), parent, cost));
+                entry.insert(id);
+                Some(id)
+            }
+        }
+    }
+
+    fn path_to(&self, id: usize) -> Path<N, C> {
+        let cost = self.nodes[id].2;
+        let mut nodes = std::iter::successors(Some(id), |&id| self.nodes[id].1)
+            .map(|id| self.nodes[id].0.clone())
+            .collect::<Vec<_>>();
+        nodes.reverse();
+        Path { cost, nodes }
+    }
+}
+
+/// Breadth first search from one or multiple start nodes, where every step costs `1`.
+///
+/// # Arguments
+///
+/// * `starts` - The nodes the search starts at. The returned path begins at one of them.
+/// * `neighbors` - Returns all nodes that are reachable from the given node in one step.
+/// * `is_goal` - Whether the given node is a goal node.
+/// * `visit` - Gets called with every node in the order it gets expanded, together with its distance.
+///   Useful for rendering the search frontier.
+///
+/// # Returns
+///
+/// The shortest path to the closest goal node, or `None` if no goal node is reachable.
+pub fn bfs_with_visitor<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+    mut visit: impl FnMut(&N, usize),
+) -> Option<Path<N, usize>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut discovered = Discovered::new();
+    let mut queue = starts
+        .into_iter()
+        .filter_map(|start| discovered.discover(start, None, 0))
+        .collect::<VecDeque<_>>();
+
+    while let Some(id) = queue.pop_front() {
+        let (node, _, distance) = &discovered.nodes[id];
+        let (node, distance) = (node.clone(), *distance);
+
+        visit(&node, distance);
+        if is_goal(&node) {
+            return Some(discovered.path_to(id));
+        }
+
+        for neighbor in neighbors(&node) {
+            if let Some(neighbor_id) = discovered.discover(neighbor, Some(id), distance + 1) {
+                queue.push_back(neighbor_id);
+            }
+        }
+    }
+
+    None
+}
+
+/// Breadth first search, see [`bfs_with_visitor`].
+#[allow(dead_code)]
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, usize>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    bfs_with_visitor(starts, neighbors, is_goal, |_, _| ())
+}
+
+/// Computes the distance from the closest start node to every reachable node.
+#[allow(dead_code)]
+pub fn bfs_distances<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbors: impl FnMut(&N) -> I,
+) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::new();
+    bfs_with_visitor(
+        starts,
+        neighbors,
+        |_| false,
+        |node, distance| {
+            distances.insert(node.clone(), distance);
+        },
+    );
+    distances
+}
+
+/// A* search from one or multiple start nodes.
+///
+/// # Arguments
+///
+/// * `starts` - The nodes the search starts at. The returned path begins at one of them.
+/// * `neighbors` - Returns all nodes that are reachable from the given node, together with
+///   the cost of getting there.
+/// * `heuristic` - An estimate of the remaining cost to the closest goal node.
+///   Must never overestimate, otherwise the result might not be optimal.
+/// * `is_goal` - Whether the given node is a goal node.
+/// * `visit` - Gets called with every node in the order it gets expanded, together with its cost.
+///   Useful for rendering the search frontier.
+///
+/// # Returns
+///
+/// The cheapest path to a goal node, or `None` if no goal node is reachable.
+pub fn astar_with_visitor<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+    mut visit: impl FnMut(&N, C),
+) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut discovered = Discovered::new();
+    let mut queue = BinaryHeap::new();
+
+    for start in starts {
+        if let Some(id) = discovered.discover(start.clone(), None, C::default()) {
+            queue.push(Reverse((heuristic(&start), C::default(), id)));
+        }
+    }
+
+    while let Some(Reverse((_, cost, id))) = queue.pop() {
+        let (node, _, best_cost) = &discovered.nodes[id];
+        if cost > *best_cost {
+            // Outdated queue entry, the node was reached more cheaply in the meantime
+            continue;
+        }
+        let node = node.clone();
+
+        visit(&node, cost);
+        if is_goal(&node) {
+            return Some(discovered.path_to(id));
+        }
+
+        for (neighbor, step_cost) in neighbors(&node) {
+            let neighbor_cost = cost + step_cost;
+            let estimate = neighbor_cost + heuristic(&neighbor);
+            if let Some(neighbor_id) = discovered.discover(neighbor, Some(id), neighbor_cost) {
+                queue.push(Reverse((estimate, neighbor_cost, neighbor_id)));
+            }
+        }
+    }
+
+    None
+}
+
+/// A* search, see [`astar_with_visitor`].
+#[allow(dead_code)]
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbors: impl FnMut(&N) -> I,
+    heuristic: impl FnMut(&N) -> C,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar_with_visitor(starts, neighbors, heuristic, is_goal, |_, _| ())
+}
+
+/// Dijkstra's algorithm; A* search without a heuristic, see [`astar_with_visitor`].
+#[allow(dead_code)]
+pub fn dijkstra_with_visitor<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+    visit: impl FnMut(&N, C),
+) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar_with_visitor(starts, neighbors, |_| C::default(), is_goal, visit)
+}
+
+/// Dijkstra's algorithm, see [`dijkstra_with_visitor`].
+#[allow(dead_code)]
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    dijkstra_with_visitor(starts, neighbors, is_goal, |_, _| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::{
+        grid::{Grid, Point},
+        input_parsing::{parse_as_2d_matrix_with_markers, Marker},
+    };
+
+    const MAZE: &str = "\
+S.#.....
+.##.###.
+....#...
+.####.#.
+......#E";
+
+    fn maze() -> (Grid<bool>, Point, Point) {
+        let (walls, markers) = parse_as_2d_matrix_with_markers(
+            MAZE,
+            0,
+            true,
+            &[Marker::new('S'), Marker::new('E')],
+            |c| Some(c == '#'),
+        )
+        .unwrap();
+        (
+            walls.into(),
+            markers[&'S'][0].try_into().unwrap(),
+            markers[&'E'][0].try_into().unwrap(),
+        )
+    }
+
+    #[test]
+    fn bfs_finds_shortest_path() {
+        let (walls, start, end) = maze();
+
+        let path = bfs(
+            [start],
+            |&p| {
+                walls
+                    .neighbors4(p)
+                    .filter(|(_, &wall)| !wall)
+                    .map(|(p, _)| p)
+                    .collect::<Vec<_>>()
+            },
+            |&p| p == end,
+        )
+        .unwrap();
+
+        assert_eq!(path.cost, 15);
+        assert_eq!(path.nodes.len(), 16);
+        assert_eq!(path.nodes.first(), Some(&start));
+        assert_eq!(path.nodes.last(), Some(&end));
+        assert!(path
+            .nodes
+            .windows(2)
+            .all(|w| w[0].manhattan_distance(w[1]) == 1));
+    }
+
+    #[test]
+    fn multi_source_and_distances() {
+        let neighbors = |&n: &i32| [n - 1, n + 1].into_iter().filter(|n| (0..=10).contains(n));
+
+        let path = bfs([0, 10], neighbors, |&n| n == 7).unwrap();
+        assert_eq!(path.nodes, vec![10, 9, 8, 7]);
+
+        let distances = bfs_distances([0, 10], neighbors);
+        assert_eq!(distances.len(), 11);
+        assert_eq!(distances[&5], 5);
+        assert_eq!(distances[&6], 4);
+    }
+
+    #[test]
+    fn weighted() {
+        // 0 -> 1 is expensive, the detour over 2 and 3 is cheaper.
+        let edges = |&n: &u8| -> Vec<(u8, u32)> {
+            match n {
+                0 => vec![(1, 10), (2, 1)],
+                2 => vec![(3, 1)],
+                3 => vec![(1, 1)],
+                _ => vec![],
+            }
+        };
+
+        let mut visited = vec![];
+        let path = dijkstra_with_visitor([0], edges, |&n| n == 1, |&n, _| visited.push(n)).unwrap();
+        assert_eq!(
+            path,
+            Path {
+                cost: 3,
+                nodes: vec![0, 2, 3, 1]
+            }
+        );
+        assert_eq!(visited, vec![0, 2, 3, 1]);
+
+        let path = astar([0], edges, |&n| u32::from(n != 1), |&n| n == 1).unwrap();
+        assert_eq!(path.cost, 3);
+
+        assert_eq!(dijkstra([1], edges, |&n| n == 0), None);
+    }
+}
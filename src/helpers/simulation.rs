@@ -0,0 +1,349 @@
+use super::{
+    accu_iter::AccuIter,
+    rendering::{Collector, RenderError, Renderable},
+};
+
+/// A simulation that advances its state in discrete steps.
+///
+/// Unlike [`AccuIter`], the state gets modified in place, so no new state
+/// has to be created for every step. Existing [`AccuIter`]s can still be run
+/// by a [`Driver`], see [`AccuSimulation`].
+pub trait Simulation {
+    type State;
+
+    fn state(&self) -> &Self::State;
+
+    /// Advances the state by one step.
+    fn step(&mut self);
+
+    /// Whether the simulation has come to an end; no further steps will be taken.
+    fn is_finished(&self) -> bool {
+        false
+    }
+}
+
+/// A [`Simulation`] made from a state and a step function, see [`simulation`].
+pub struct FnSimulation<S, F>
+where
+    F: FnMut(&mut S) -> bool,
+{
+    state: S,
+    step: F,
+    finished: bool,
+}
+
+impl<S, F> Simulation for FnSimulation<S, F>
+where
+    F: FnMut(&mut S) -> bool,
+{
+    type State = S;
+
+    fn state(&self) -> &S {
+        &self.state
+    }
+
+    fn step(&mut self) {
+        self.finished = !(self.step)(&mut self.state);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Creates a simulation from an initial state and a step function,
+/// similar to [`super::accu_iter::accu_iter`].
+///
+/// The step function modifies the state in place, and returns `false`
+/// once the simulation is finished.
+#[allow(dead_code)]
+pub fn simulation<S, F>(initial: S, step: F) -> Driver<FnSimulation<S, F>>
+where
+    F: FnMut(&mut S) -> bool,
+{
+    Driver::new(FnSimulation {
+        state: initial,
+        step,
+        finished: false,
+    })
+}
+
+/// A [`Simulation`] whose state is the accumulator of an [`AccuIter`].
+///
+/// Every step advances the iterator; the simulation is finished once it returns `None`.
+pub struct AccuSimulation<Accu, Func, Res>
+where
+    Func: Fn(&Accu) -> (Accu, Option<Res>),
+{
+    iter: AccuIter<Accu, Func, Res>,
+    finished: bool,
+}
+
+impl<Accu, Func, Res> Simulation for AccuSimulation<Accu, Func, Res>
+where
+    Func: Fn(&Accu) -> (Accu, Option<Res>),
+{
+    type State = Accu;
+
+    fn state(&self) -> &Accu {
+        self.iter.accu()
+    }
+
+    fn step(&mut self) {
+        self.finished = self.iter.next().is_none();
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+impl<Accu, Func, Res> From<AccuIter<Accu, Func, Res>> for Driver<AccuSimulation<Accu, Func, Res>>
+where
+    Func: Fn(&Accu) -> (Accu, Option<Res>),
+{
+    fn from(iter: AccuIter<Accu, Func, Res>) -> Self {
+        Driver::new(AccuSimulation {
+            iter,
+            finished: false,
+        })
+    }
+}
+
+/// How a call to [`Driver::run`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The simulation finished after the given total number of steps.
+    Finished(usize),
+    /// The step limit was reached before the simulation finished.
+    LimitReached(usize),
+}
+
+/// Runs a [`Simulation`], optionally with a step limit and a history of all states.
+///
+/// The driver is an iterator over the number of steps taken, so it can be
+/// combined with the usual iterator adapters.
+pub struct Driver<S: Simulation> {
+    simulation: S,
+    steps: usize,
+    max_steps: Option<usize>,
+    history: Option<History<S::State>>,
+}
+
+struct History<T> {
+    states: Vec<T>,
+    record: fn(&T) -> T,
+}
+
+#[allow(dead_code)]
+impl<S: Simulation> Driver<S> {
+    pub fn new(simulation: S) -> Self {
+        Self {
+            simulation,
+            steps: 0,
+            max_steps: None,
+            history: None,
+        }
+    }
+
+    /// Stops the simulation after the given total number of steps.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Records a copy of every state, including the initial one.
+    pub fn with_history(mut self) -> Self
+    where
+        S::State: Clone,
+    {
+        self.history = Some(History {
+            states: vec![self.simulation.state().clone()],
+            record: S::State::clone,
+        });
+        self
+    }
+
+    pub fn simulation(&self) -> &S {
+        &self.simulation
+    }
+
+    pub fn into_simulation(self) -> S {
+        self.simulation
+    }
+
+    pub fn state(&self) -> &S::State {
+        self.simulation.state()
+    }
+
+    /// The number of steps taken so far.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// All recorded states, if the history is enabled.
+    pub fn history(&self) -> Option<&[S::State]> {
+        self.history
+            .as_ref()
+            .map(|history| history.states.as_slice())
+    }
+
+    fn can_step(&self) -> bool {
+        !self.simulation.is_finished() && self.max_steps.is_none_or(|max| self.steps < max)
+    }
+
+    /// Advances the simulation by one step, unless it is finished or the step limit is reached.
+    ///
+    /// # Returns
+    ///
+    /// The new state, or `None` if no step was taken.
+    pub fn step(&mut self) -> Option<&S::State> {
+        if !self.can_step() {
+            return None;
+        }
+
+        self.simulation.step();
+        self.steps += 1;
+
+        if let Some(history) = &mut self.history {
+            history
+                .states
+                .push((history.record)(self.simulation.state()));
+        }
+
+        Some(self.simulation.state())
+    }
+
+    fn outcome(&self) -> Outcome {
+        if self.simulation.is_finished() {
+            Outcome::Finished(self.steps)
+        } else {
+            Outcome::LimitReached(self.steps)
+        }
+    }
+
+    /// Runs the simulation until it is finished or the step limit is reached.
+    pub fn run(&mut self) -> Outcome {
+        while self.step().is_some() {}
+        self.outcome()
+    }
+
+    /// Like [`Driver::run`], but adds every state, including the initial one,
+    /// as a frame to the given collector.
+    ///
+    /// # Arguments
+    ///
+    /// * `collector` - The collector that receives the frames.
+    /// * `frame_time` - The time between two frames, in seconds.
+    ///
+    /// # Returns
+    ///
+    /// How the simulation ended, or the first error of the collector.
+    pub fn run_rendered(
+        &mut self,
+        collector: &mut impl Collector,
+        frame_time: f64,
+    ) -> Result<Outcome, RenderError>
+    where
+        S::State: Renderable,
+    {
+        let mut timestamp = self.steps as f64 * frame_time;
+        self.state().add_to(collector, timestamp)?;
+
+        while let Some(state) = self.step() {
+            timestamp += frame_time;
+            state.add_to(collector, timestamp)?;
+        }
+
+        Ok(self.outcome())
+    }
+}
+
+impl<S: Simulation> Iterator for Driver<S> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.step()?;
+        Some(self.steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::{accu_iter::accu_iter, rendering::FramesCounter};
+    use ndarray::Array2;
+
+    #[test]
+    fn finishes() {
+        let mut driver = simulation(1u64, |n| {
+            *n = if *n % 2 == 0 { *n / 2 } else { *n * 3 + 1 };
+            *n != 1
+        });
+
+        // 1 -> 4 -> 2 -> 1
+        assert_eq!(driver.run(), Outcome::Finished(3));
+        assert_eq!(driver.step(), None);
+    }
+
+    #[test]
+    fn limit_and_history() {
+        let mut driver = simulation(0u32, |n| {
+            *n += 1;
+            true
+        })
+        .with_max_steps(3)
+        .with_history();
+
+        assert_eq!(driver.by_ref().last(), Some(3));
+        assert_eq!(driver.run(), Outcome::LimitReached(3));
+        assert_eq!(driver.history(), Some(&[0, 1, 2, 3][..]));
+    }
+
+    #[test]
+    fn drives_accu_iter() {
+        let mut driver = Driver::from(accu_iter(3u32, |&n| {
+            (n.saturating_sub(1), (n > 0).then_some(n))
+        }))
+        .with_history();
+
+        // The step that returns `None` still updates the accumulator
+        assert_eq!(driver.run(), Outcome::Finished(4));
+        assert_eq!(driver.history(), Some(&[3, 2, 1, 0, 0][..]));
+
+        let mut driver = Driver::from(accu_iter(0u32, |&n| (n + 1, Some(n)))).with_max_steps(10);
+        assert_eq!(driver.run(), Outcome::LimitReached(10));
+        assert_eq!(*driver.state(), 10);
+    }
+
+    #[test]
+    fn renders_every_state() {
+        #[derive(Clone, Copy)]
+        struct Lit(bool);
+        impl crate::helpers::rendering::ToColor for Lit {
+            fn to_color(&self) -> rgb::RGBA8 {
+                rgb::RGBA8::new(0, 0, 0, if self.0 { 255 } else { 0 })
+            }
+        }
+
+        struct Blink(Array2<Lit>);
+        impl Simulation for Blink {
+            type State = Array2<Lit>;
+            fn state(&self) -> &Array2<Lit> {
+                &self.0
+            }
+            fn step(&mut self) {
+                self.0.mapv_inplace(|v| Lit(!v.0));
+            }
+        }
+
+        let mut collector = FramesCounter::new();
+        let outcome = Driver::new(Blink(Array2::from_elem((2, 2), Lit(false))))
+            .with_max_steps(5)
+            .run_rendered(&mut collector, 0.1)
+            .unwrap();
+
+        assert_eq!(outcome, Outcome::LimitReached(5));
+        assert_eq!(collector.get_num_frames(), 6);
+    }
+}
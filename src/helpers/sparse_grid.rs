@@ -0,0 +1,302 @@
+use std::{collections::HashMap, fmt};
+
+use ndarray::Array2;
+
+use super::{
+    grid::{Grid, Point},
+    rendering::{Collector, RenderError, ToColor},
+};
+
+/// An inclusive, rectangular region of the unbounded plane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+#[allow(dead_code)]
+impl Bounds {
+    pub fn new(min: Point, max: Point) -> Self {
+        assert!(min.row <= max.row && min.col <= max.col);
+        Self { min, max }
+    }
+
+    /// A region with the given size, whose center is `center`.
+    pub fn centered_on(center: Point, rows: usize, cols: usize) -> Self {
+        let (rows, cols) = (rows.max(1) as isize, cols.max(1) as isize);
+        let min = center - Point::new(rows / 2, cols / 2);
+        Self::new(min, min + Point::new(rows - 1, cols - 1))
+    }
+
+    pub fn point(point: Point) -> Self {
+        Self::new(point, point)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.max.row.abs_diff(self.min.row) + 1
+    }
+
+    pub fn cols(&self) -> usize {
+        self.max.col.abs_diff(self.min.col) + 1
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.row..=self.max.row).contains(&point.row)
+            && (self.min.col..=self.max.col).contains(&point.col)
+    }
+
+    pub fn extend(&mut self, point: Point) {
+        self.min = Point::new(self.min.row.min(point.row), self.min.col.min(point.col));
+        self.max = Point::new(self.max.row.max(point.row), self.max.col.max(point.col));
+    }
+
+    pub fn union(mut self, other: Bounds) -> Self {
+        self.extend(other.min);
+        self.extend(other.max);
+        self
+    }
+
+    pub fn grow(self, margin: usize) -> Self {
+        let margin = Point::new(margin as isize, margin as isize);
+        Self::new(self.min - margin, self.max + margin)
+    }
+
+    /// The smallest shift that moves this region so that it contains `point`.
+    pub fn follow(self, point: Point) -> Self {
+        let shift = Point::new(
+            (point.row - self.max.row).max(0) + (point.row - self.min.row).min(0),
+            (point.col - self.max.col).max(0) + (point.col - self.min.col).min(0),
+        );
+        Self::new(self.min + shift, self.max + shift)
+    }
+
+    /// All points of the region, row by row.
+    pub fn points(self) -> impl Iterator<Item = Point> {
+        (self.min.row..=self.max.row)
+            .flat_map(move |row| (self.min.col..=self.max.col).map(move |col| Point::new(row, col)))
+    }
+}
+
+/// An unbounded grid that only stores the cells that were set.
+///
+/// Keeps track of the bounding box of all of its cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The smallest region containing all cells, or `None` if the grid is empty.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.cells.get(&point)
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.cells.get_mut(&point)
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.cells.contains_key(&point)
+    }
+
+    pub fn insert(&mut self, point: Point, value: T) -> Option<T> {
+        match &mut self.bounds {
+            Some(bounds) => bounds.extend(point),
+            None => self.bounds = Some(Bounds::point(point)),
+        }
+        self.cells.insert(point, value)
+    }
+
+    pub fn remove(&mut self, point: Point) -> Option<T> {
+        let removed = self.cells.remove(&point)?;
+
+        let on_edge = self.bounds.is_some_and(|bounds| {
+            point.row == bounds.min.row
+                || point.row == bounds.max.row
+                || point.col == bounds.min.col
+                || point.col == bounds.max.col
+        });
+        if on_edge {
+            self.bounds = self.cells.keys().fold(None, |bounds, &point| match bounds {
+                Some(mut bounds) => {
+                    bounds.extend(point);
+                    Some(bounds)
+                }
+                None => Some(Bounds::point(point)),
+            });
+        }
+
+        Some(removed)
+    }
+
+    pub fn entry_or_insert_with(&mut self, point: Point, f: impl FnOnce() -> T) -> &mut T {
+        if !self.cells.contains_key(&point) {
+            self.insert(point, f());
+        }
+        self.cells.get_mut(&point).unwrap()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(&point, value)| (point, value))
+    }
+
+    /// The occupied orthogonal neighbors of a point.
+    pub fn neighbors4(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        point
+            .neighbors4()
+            .filter_map(|neighbor| self.get(neighbor).map(|value| (neighbor, value)))
+    }
+
+    /// The occupied orthogonal and diagonal neighbors of a point.
+    pub fn neighbors8(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        point
+            .neighbors8()
+            .filter_map(|neighbor| self.get(neighbor).map(|value| (neighbor, value)))
+    }
+
+    /// Converts the given region of the grid to a dense array.
+    ///
+    /// The top left corner of the viewport ends up at index `(0, 0)`.
+    pub fn to_array<U>(&self, viewport: Bounds, mut f: impl FnMut(Option<&T>) -> U) -> Array2<U> {
+        Array2::from_shape_fn((viewport.rows(), viewport.cols()), |index| {
+            // The size of the viewport is computed from points, so its indices fit into isize
+            let offset = Point::try_from(index).expect("Viewport indices always fit into isize");
+            f(self.get(viewport.min + offset))
+        })
+    }
+
+    /// Converts the entire grid to a dense grid, together with the offset of its top left corner.
+    pub fn to_grid<U>(&self, f: impl FnMut(Option<&T>) -> U) -> (Grid<U>, Point) {
+        let viewport = self.bounds.unwrap_or_else(|| Bounds::point(Point::ORIGIN));
+        (Grid::new(self.to_array(viewport, f)), viewport.min)
+    }
+
+    /// Adds the given region of the grid as a frame to a rendering collector.
+    pub fn add_frame<U: ToColor>(
+        &self,
+        collector: &mut impl Collector,
+        viewport: Bounds,
+        timestamp: f64,
+        f: impl FnMut(Option<&T>) -> U,
+    ) -> Result<(), RenderError> {
+        collector.add_frame(&self.to_array(viewport, f), timestamp)
+    }
+
+    /// Renders the given region of the grid as text, one line per row.
+    pub fn render_text(&self, viewport: Bounds, mut f: impl FnMut(Option<&T>) -> char) -> String {
+        let mut text = String::with_capacity(viewport.rows() * (viewport.cols() + 1));
+        for row in viewport.min.row..=viewport.max.row {
+            for col in viewport.min.col..=viewport.max.col {
+                text.push(f(self.get(Point::new(row, col))));
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        for (point, value) in iter {
+            grid.insert(point, value);
+        }
+        grid
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(bounds) = self.bounds {
+            for row in bounds.min.row..=bounds.max.row {
+                for col in bounds.min.col..=bounds.max.col {
+                    match self.get(Point::new(row, col)) {
+                        Some(value) => write!(f, "{}", value)?,
+                        None => write!(f, ".")?,
+                    }
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_are_tracked() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.bounds(), None);
+
+        grid.insert(Point::new(-2, 3), '#');
+        grid.insert(Point::new(1, -1), '#');
+        grid.insert(Point::new(0, 0), '#');
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds::new(Point::new(-2, -1), Point::new(1, 3)))
+        );
+
+        grid.remove(Point::new(-2, 3));
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds::new(Point::new(0, -1), Point::new(1, 0)))
+        );
+    }
+
+    #[test]
+    fn dense_viewport() {
+        let grid = [(Point::new(-1, -1), 1), (Point::new(0, 1), 2)]
+            .into_iter()
+            .collect::<SparseGrid<_>>();
+
+        let array = grid.to_array(Bounds::centered_on(Point::ORIGIN, 3, 3), |v| {
+            v.copied().unwrap_or(0)
+        });
+        assert_eq!(array, ndarray::array![[1, 0, 0], [0, 0, 2], [0, 0, 0]]);
+
+        assert_eq!(grid.to_string(), "1..\n..2\n");
+        assert_eq!(grid.neighbors8(Point::ORIGIN).count(), 2);
+        assert_eq!(grid.neighbors4(Point::ORIGIN).count(), 1);
+    }
+
+    #[test]
+    fn follow() {
+        let viewport = Bounds::new(Point::new(0, 0), Point::new(4, 4));
+
+        assert_eq!(viewport.follow(Point::new(2, 2)), viewport);
+        assert_eq!(
+            viewport.follow(Point::new(-2, 6)),
+            Bounds::new(Point::new(-2, 2), Point::new(2, 6))
+        );
+    }
+}
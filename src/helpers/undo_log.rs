@@ -0,0 +1,410 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    ops::Deref,
+};
+
+use ndarray::Array2;
+
+type Undo<S> = Box<dyn FnOnce(&mut S)>;
+
+/// Projects the state of an [`UndoLog`] onto one of its containers.
+///
+/// Usually a non-capturing closure, like `|s: &mut State| &mut s.visited`.
+pub type Field<S, T> = fn(&mut S) -> &mut T;
+
+/// Owns a state and records how to revert every change made to it.
+///
+/// Changes can only be made through a [`Checkpoint`] guard. When the guard is destroyed,
+/// all changes made through it are undone in reverse order.
+///
+/// This generalizes [`super::temporary_hashset::TemporaryHashSet`] to multiple changes per guard,
+/// nested guards and arbitrary containers, as needed for backtracking searches.
+/// If the state consists of multiple containers, put them into a struct or tuple,
+/// and select the one to modify with a [`Field`] projection.
+pub struct UndoLog<S> {
+    state: S,
+    log: Vec<Undo<S>>,
+    depth: usize,
+}
+
+/// A guard that rolls back all changes made through it, once dropped.
+///
+/// Can be dereferenced to the current state.
+pub struct Checkpoint<'a, S> {
+    undo_log: &'a mut UndoLog<S>,
+    start: usize,
+}
+
+#[allow(dead_code)]
+impl<S> UndoLog<S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            log: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn into_inner(self) -> S {
+        self.state
+    }
+
+    /// Starts recording changes.
+    pub fn checkpoint(&mut self) -> Checkpoint<'_, S> {
+        self.depth += 1;
+        Checkpoint {
+            start: self.log.len(),
+            undo_log: self,
+        }
+    }
+}
+
+impl<'a, S> Deref for Checkpoint<'a, S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.undo_log.state
+    }
+}
+
+#[allow(dead_code)]
+impl<'a, S: 'static> Checkpoint<'a, S> {
+    /// Starts a nested checkpoint; dropping it only undoes the changes made through it.
+    pub fn checkpoint(&mut self) -> Checkpoint<'_, S> {
+        self.undo_log.checkpoint()
+    }
+
+    /// The number of changes recorded by this checkpoint and its committed children.
+    pub fn changes(&self) -> usize {
+        self.undo_log.log.len() - self.start
+    }
+
+    /// Keeps all changes made through this checkpoint.
+    ///
+    /// For nested checkpoints, the changes become part of the parent checkpoint
+    /// and will still be undone when the parent gets dropped.
+    pub fn commit(self) {
+        let mut this = std::mem::ManuallyDrop::new(self);
+        this.undo_log.depth -= 1;
+        if this.undo_log.depth == 0 {
+            this.undo_log.log.clear();
+        }
+    }
+
+    /// Undoes all changes made through this checkpoint so far, but keeps recording.
+    pub fn rollback(&mut self) {
+        let undo_log = &mut *self.undo_log;
+        for undo in undo_log.log.drain(self.start..).rev() {
+            undo(&mut undo_log.state);
+        }
+    }
+
+    fn record(&mut self, undo: impl FnOnce(&mut S) + 'static) {
+        self.undo_log.log.push(Box::new(undo));
+    }
+
+    /// Replaces a value, e.g. a counter.
+    ///
+    /// # Returns
+    ///
+    /// The previous value.
+    pub fn set<T>(&mut self, field: Field<S, T>, value: T) -> T
+    where
+        T: Clone + 'static,
+    {
+        let previous = std::mem::replace(field(&mut self.undo_log.state), value);
+        let restored = previous.clone();
+        self.record(move |state| *field(state) = restored);
+        previous
+    }
+
+    /// Modifies a value in place.
+    pub fn update<T>(&mut self, field: Field<S, T>, f: impl FnOnce(&mut T))
+    where
+        T: Clone + 'static,
+    {
+        let value = field(&mut self.undo_log.state);
+        let previous = value.clone();
+        f(value);
+        self.record(move |state| *field(state) = previous);
+    }
+
+    /// Inserts an element into a set.
+    ///
+    /// # Returns
+    ///
+    /// Whether the element was newly inserted.
+    pub fn insert<T>(&mut self, field: Field<S, HashSet<T>>, value: T) -> bool
+    where
+        T: Eq + Hash + Clone + 'static,
+    {
+        let inserted = field(&mut self.undo_log.state).insert(value.clone());
+        if inserted {
+            self.record(move |state| {
+                field(state).remove(&value);
+            });
+        }
+        inserted
+    }
+
+    /// Removes an element from a set.
+    ///
+    /// # Returns
+    ///
+    /// Whether the element was present.
+    pub fn remove<T>(&mut self, field: Field<S, HashSet<T>>, value: &T) -> bool
+    where
+        T: Eq + Hash + Clone + 'static,
+    {
+        let removed = field(&mut self.undo_log.state).take(value);
+        let was_present = removed.is_some();
+        if let Some(removed) = removed {
+            self.record(move |state| {
+                field(state).insert(removed);
+            });
+        }
+        was_present
+    }
+
+    /// Inserts a key-value pair into a map.
+    ///
+    /// # Returns
+    ///
+    /// The value previously stored for the key.
+    pub fn map_insert<K, V>(
+        &mut self,
+        field: Field<S, HashMap<K, V>>,
+        key: K,
+        value: V,
+    ) -> Option<V>
+    where
+        K: Eq + Hash + Clone + 'static,
+        V: Clone + 'static,
+    {
+        let previous = field(&mut self.undo_log.state).insert(key.clone(), value);
+        let restored = previous.clone();
+        self.record(move |state| match restored {
+            Some(restored) => {
+                field(state).insert(key, restored);
+            }
+            None => {
+                field(state).remove(&key);
+            }
+        });
+        previous
+    }
+
+    /// Removes a key from a map.
+    ///
+    /// # Returns
+    ///
+    /// The value that was stored for the key.
+    pub fn map_remove<K, V>(&mut self, field: Field<S, HashMap<K, V>>, key: &K) -> Option<V>
+    where
+        K: Eq + Hash + Clone + 'static,
+        V: Clone + 'static,
+    {
+        let (key, previous) = field(&mut self.undo_log.state).remove_entry(key)?;
+        let restored = previous.clone();
+        self.record(move |state| {
+            field(state).insert(key, restored);
+        });
+        Some(previous)
+    }
+
+    /// Pushes an element onto a stack.
+    pub fn push<T>(&mut self, field: Field<S, Vec<T>>, value: T)
+    where
+        T: 'static,
+    {
+        field(&mut self.undo_log.state).push(value);
+        self.record(move |state| {
+            field(state).pop();
+        });
+    }
+
+    /// Pops an element from a stack.
+    pub fn pop<T>(&mut self, field: Field<S, Vec<T>>) -> Option<T>
+    where
+        T: Clone + 'static,
+    {
+        let value = field(&mut self.undo_log.state).pop()?;
+        let restored = value.clone();
+        self.record(move |state| field(state).push(restored));
+        Some(value)
+    }
+
+    /// Replaces a cell of a grid.
+    ///
+    /// # Returns
+    ///
+    /// The previous value of the cell.
+    pub fn set_cell<T>(&mut self, field: Field<S, Array2<T>>, index: (usize, usize), value: T) -> T
+    where
+        T: Clone + 'static,
+    {
+        let previous = std::mem::replace(&mut field(&mut self.undo_log.state)[index], value);
+        let restored = previous.clone();
+        self.record(move |state| field(state)[index] = restored);
+        previous
+    }
+}
+
+impl<'a, S> Drop for Checkpoint<'a, S> {
+    fn drop(&mut self) {
+        let undo_log = &mut *self.undo_log;
+        for undo in undo_log.log.drain(self.start..).rev() {
+            undo(&mut undo_log.state);
+        }
+        undo_log.depth -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::input_parsing::{parse_as_2d_matrix_with_filled_border, RaggedRows};
+
+    #[test]
+    fn nested() {
+        let mut log = UndoLog::new((0u32, vec![1, 2], HashMap::from([('a', 1)])));
+
+        {
+            let mut outer = log.checkpoint();
+            outer.set(|s| &mut s.0, 5);
+            outer.push(|s| &mut s.1, 3);
+            {
+                let mut inner = outer.checkpoint();
+                inner.update(|s| &mut s.0, |v| *v += 1);
+                inner.map_insert(|s| &mut s.2, 'a', 10);
+                inner.map_insert(|s| &mut s.2, 'b', 20);
+                assert_eq!(
+                    *inner,
+                    (6, vec![1, 2, 3], HashMap::from([('a', 10), ('b', 20)]))
+                );
+            }
+            assert_eq!(*outer, (5, vec![1, 2, 3], HashMap::from([('a', 1)])));
+            {
+                let mut inner = outer.checkpoint();
+                inner.pop(|s| &mut s.1);
+                inner.map_remove(|s| &mut s.2, &'a');
+                inner.commit();
+            }
+            assert_eq!(*outer, (5, vec![1, 2], HashMap::new()));
+        }
+
+        assert_eq!(*log.state(), (0, vec![1, 2], HashMap::from([('a', 1)])));
+
+        log.checkpoint().commit();
+        let mut checkpoint = log.checkpoint();
+        checkpoint.set(|s| &mut s.0, 7);
+        checkpoint.commit();
+        assert_eq!(log.state().0, 7);
+    }
+
+    /// Marks the open region around `pos`, in a checkpoint that can later undo the whole fill.
+    fn fill(checkpoint: &mut Checkpoint<Array2<char>>, pos: (usize, usize)) -> usize {
+        if checkpoint[pos] != '.' {
+            return 0;
+        }
+        checkpoint.set_cell(|map| map, pos, 'o');
+
+        1 + [(0, 1), (2, 1), (1, 0), (1, 2)]
+            .into_iter()
+            .map(|(dy, dx)| fill(checkpoint, (pos.0 + dy - 1, pos.1 + dx - 1)))
+            .sum::<usize>()
+    }
+
+    fn filled(map: &Array2<char>) -> HashSet<(usize, usize)> {
+        map.indexed_iter()
+            .filter(|(_, &c)| c == 'o')
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    #[test]
+    fn flood_fill() {
+        // The left region is an open 2x2 block, so it can be reached along a cycle
+        let map = parse_as_2d_matrix_with_filled_border::<char>(
+            "..#.\n..#.\n##..",
+            1,
+            '#',
+            RaggedRows::Strict,
+        )
+        .unwrap();
+        let left = HashSet::from([(1, 1), (1, 2), (2, 1), (2, 2)]);
+        let right = HashSet::from([(1, 4), (2, 4), (3, 3), (3, 4)]);
+        let mut log = UndoLog::new(map.clone());
+
+        {
+            let mut outer = log.checkpoint();
+            assert_eq!(fill(&mut outer, (1, 1)), 4);
+            assert_eq!(filled(&outer), left);
+            let after_left = outer.clone();
+
+            {
+                let mut inner = outer.checkpoint();
+                assert_eq!(fill(&mut inner, (3, 3)), 4);
+                assert_eq!(fill(&mut inner, (2, 2)), 0);
+                assert_eq!(filled(&inner), &left | &right);
+
+                inner.rollback();
+                assert_eq!(*inner, after_left);
+                assert_eq!(fill(&mut inner, (1, 4)), 4);
+            }
+            assert_eq!(*outer, after_left);
+
+            outer.rollback();
+            assert_eq!(*outer, map);
+            assert_eq!(fill(&mut outer, (2, 2)), 4);
+        }
+
+        assert_eq!(*log.state(), map);
+    }
+
+    #[test]
+    fn permutations() {
+        #[derive(Default)]
+        struct State {
+            used: HashSet<u8>,
+            current: Vec<u8>,
+        }
+
+        fn permute(checkpoint: &mut Checkpoint<State>, items: &[u8], found: &mut Vec<Vec<u8>>) {
+            if checkpoint.current.len() == items.len() {
+                found.push(checkpoint.current.clone());
+                return;
+            }
+            for &item in items {
+                let mut choice = checkpoint.checkpoint();
+                if choice.insert(|s| &mut s.used, item) {
+                    choice.push(|s| &mut s.current, item);
+                    permute(&mut choice, items, found);
+                }
+            }
+        }
+
+        let mut log = UndoLog::new(State::default());
+        let mut found = vec![];
+        permute(&mut log.checkpoint(), &[1, 2, 3], &mut found);
+
+        assert_eq!(
+            found,
+            vec![
+                vec![1, 2, 3],
+                vec![1, 3, 2],
+                vec![2, 1, 3],
+                vec![2, 3, 1],
+                vec![3, 1, 2],
+                vec![3, 2, 1],
+            ]
+        );
+        assert!(log.state().used.is_empty());
+        assert!(log.state().current.is_empty());
+    }
+}
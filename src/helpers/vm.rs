@@ -0,0 +1,407 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use thiserror::Error;
+
+mod parser {
+    use super::{Instruction, Operand};
+
+    pub use crate::helpers::nom::*;
+
+    fn register(input: &str) -> VResult<'_, char> {
+        satisfy(|c| c.is_ascii_alphabetic())(input)
+    }
+
+    fn operand(input: &str) -> VResult<'_, Operand> {
+        alt((
+            map(i64, Operand::Immediate),
+            map(register, Operand::Register),
+        ))(input)
+    }
+
+    fn instruction(input: &str) -> VResult<'_, Instruction> {
+        map(
+            pair(
+                alpha1,
+                separated_list0(
+                    alt((tag(", "), tag(","), space1)),
+                    preceded(space0, operand),
+                ),
+            ),
+            |(opcode, operands): (&str, _)| Instruction {
+                opcode: opcode.to_string(),
+                operands,
+            },
+        )(input)
+    }
+
+    pub fn program(input: &str) -> VResult<'_, Vec<Instruction>> {
+        separated_list1(line_ending, instruction)(input)
+    }
+}
+
+/// An operand of an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Register(char),
+    Immediate(i64),
+}
+
+impl Operand {
+    /// The value of the operand; the content of the register, or the immediate value.
+    pub fn value(&self, registers: &Registers) -> i64 {
+        match *self {
+            Operand::Register(register) => registers[register],
+            Operand::Immediate(value) => value,
+        }
+    }
+
+    /// The register the operand refers to.
+    ///
+    /// # Panics
+    ///
+    /// If the operand is an immediate value.
+    pub fn register(&self) -> char {
+        match *self {
+            Operand::Register(register) => register,
+            Operand::Immediate(value) => {
+                panic!("Expected a register, got immediate value {}", value)
+            }
+        }
+    }
+}
+
+/// A single instruction of a program, like `addx 3` or `cpy a, b`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: String,
+    pub operands: Vec<Operand>,
+}
+
+/// Parses a program with one instruction per line.
+///
+/// Operands are either signed integers or single letter registers,
+/// separated by spaces or commas.
+pub fn parse_program(input_data: &str) -> Vec<Instruction> {
+    let input_data = input_data.trim_end();
+    parser::finalize(input_data)(parser::program(input_data))
+}
+
+/// The registers `a` to `z`. Register names are case insensitive.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Registers([i64; 26]);
+
+impl Registers {
+    fn offset(register: char) -> usize {
+        assert!(
+            register.is_ascii_alphabetic(),
+            "Invalid register name {:?}",
+            register
+        );
+        (register.to_ascii_lowercase() as u8 - b'a').into()
+    }
+}
+
+impl Index<char> for Registers {
+    type Output = i64;
+
+    fn index(&self, register: char) -> &i64 {
+        &self.0[Self::offset(register)]
+    }
+}
+
+impl IndexMut<char> for Registers {
+    fn index_mut(&mut self, register: char) -> &mut i64 {
+        &mut self.0[Self::offset(register)]
+    }
+}
+
+/// The effect of an instruction; returned by [`Execute`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Continue with the next instruction.
+    Next,
+    /// Jump relative to the current instruction.
+    Jump(i64),
+}
+
+/// The implementation of an instruction.
+pub type Execute = Box<dyn Fn(&mut Registers, &[Operand]) -> Flow>;
+
+struct InstructionSpec {
+    operands: usize,
+    cycles: usize,
+    execute: Execute,
+}
+
+/// Describes the instructions a machine understands.
+#[derive(Default)]
+pub struct InstructionSet {
+    instructions: HashMap<String, InstructionSpec>,
+}
+
+#[allow(dead_code)]
+impl InstructionSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an instruction.
+    ///
+    /// # Arguments
+    ///
+    /// * `opcode` - The name of the instruction.
+    /// * `operands` - The number of operands the instruction requires.
+    /// * `cycles` - How many cycles the instruction takes. Its effect
+    ///   becomes visible only after the last of those cycles.
+    /// * `execute` - Applies the effect of the instruction to the registers.
+    pub fn with(
+        mut self,
+        opcode: &str,
+        operands: usize,
+        cycles: usize,
+        execute: impl Fn(&mut Registers, &[Operand]) -> Flow + 'static,
+    ) -> Self {
+        assert!(cycles > 0, "Instructions have to take at least one cycle");
+        self.instructions.insert(
+            opcode.to_string(),
+            InstructionSpec {
+                operands,
+                cycles,
+                execute: Box::new(execute),
+            },
+        );
+        self
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum VmError {
+    #[error("Unknown instruction '{opcode}' in line {line}")]
+    UnknownInstruction { opcode: String, line: usize },
+    #[error("Instruction '{opcode}' in line {line} requires {expected} operands, got {actual}")]
+    OperandCount {
+        opcode: String,
+        line: usize,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+/// Executes a program cycle by cycle.
+///
+/// Iterating over the executor yields the cycle number, starting at 1,
+/// together with the registers *during* that cycle; that is, before
+/// the instruction that finishes in that cycle takes effect.
+/// Iteration ends once the program counter leaves the program.
+pub struct Executor<'a> {
+    program: &'a [Instruction],
+    instruction_set: &'a InstructionSet,
+    registers: Registers,
+    pc: i64,
+    cycle: usize,
+    remaining_cycles: usize,
+}
+
+#[allow(dead_code)]
+impl<'a> Executor<'a> {
+    /// Creates an executor, after verifying that every instruction of the program is known.
+    pub fn new(
+        program: &'a [Instruction],
+        instruction_set: &'a InstructionSet,
+        registers: Registers,
+    ) -> Result<Self, VmError> {
+        for (line, instruction) in program.iter().enumerate() {
+            let spec = instruction_set
+                .instructions
+                .get(&instruction.opcode)
+                .ok_or_else(|| VmError::UnknownInstruction {
+                    opcode: instruction.opcode.clone(),
+                    line: line + 1,
+                })?;
+            if spec.operands != instruction.operands.len() {
+                return Err(VmError::OperandCount {
+                    opcode: instruction.opcode.clone(),
+                    line: line + 1,
+                    expected: spec.operands,
+                    actual: instruction.operands.len(),
+                });
+            }
+        }
+
+        Ok(Self {
+            program,
+            instruction_set,
+            registers,
+            pc: 0,
+            cycle: 0,
+            remaining_cycles: 0,
+        })
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    /// The number of cycles executed so far.
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    fn current(&self) -> Option<(&'a Instruction, &'a InstructionSpec)> {
+        let instruction = self.program.get(usize::try_from(self.pc).ok()?)?;
+        Some((
+            instruction,
+            &self.instruction_set.instructions[&instruction.opcode],
+        ))
+    }
+
+    /// Runs the program to the end.
+    ///
+    /// # Returns
+    ///
+    /// The final registers.
+    pub fn run(mut self) -> Registers {
+        self.by_ref().for_each(drop);
+        self.registers
+    }
+}
+
+impl<'a> Iterator for Executor<'a> {
+    type Item = (usize, Registers);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (instruction, spec) = self.current()?;
+        if self.remaining_cycles == 0 {
+            self.remaining_cycles = spec.cycles;
+        }
+
+        self.cycle += 1;
+        let item = (self.cycle, self.registers);
+
+        self.remaining_cycles -= 1;
+        if self.remaining_cycles == 0 {
+            self.pc += match (spec.execute)(&mut self.registers, &instruction.operands) {
+                Flow::Next => 1,
+                Flow::Jump(offset) => offset,
+            };
+        }
+
+        Some(item)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.opcode)?;
+        for (i, operand) in self.operands.iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            match operand {
+                Operand::Register(register) => write!(f, "{}{}", separator, register)?,
+                Operand::Immediate(value) => write!(f, "{}{}", separator, value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpu() -> InstructionSet {
+        InstructionSet::new()
+            .with("noop", 0, 1, |_, _| Flow::Next)
+            .with("addx", 1, 2, |regs, ops| {
+                regs['x'] += ops[0].value(regs);
+                Flow::Next
+            })
+    }
+
+    #[test]
+    fn cycle_accurate() {
+        let program = parse_program("noop\naddx 3\naddx -5\n");
+        let instruction_set = cpu();
+        let mut registers = Registers::default();
+        registers['x'] = 1;
+
+        let executor = Executor::new(&program, &instruction_set, registers).unwrap();
+        let x_values = executor
+            .map(|(cycle, regs)| (cycle, regs['x']))
+            .collect::<Vec<_>>();
+
+        assert_eq!(x_values, vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)]);
+        assert_eq!(
+            Executor::new(&program, &instruction_set, registers)
+                .unwrap()
+                .run()['X'],
+            -1
+        );
+    }
+
+    #[test]
+    fn jumps() {
+        // Multiplies a and b into c
+        let program =
+            parse_program("cpy 0, c\ncpy b, d\ninc c\ndec d\njnz d, -2\ndec a\njnz a, -5");
+        let instruction_set = InstructionSet::new()
+            .with("cpy", 2, 1, |regs, ops| {
+                regs[ops[1].register()] = ops[0].value(regs);
+                Flow::Next
+            })
+            .with("inc", 1, 1, |regs, ops| {
+                regs[ops[0].register()] += 1;
+                Flow::Next
+            })
+            .with("dec", 1, 1, |regs, ops| {
+                regs[ops[0].register()] -= 1;
+                Flow::Next
+            })
+            .with("jnz", 2, 1, |regs, ops| match ops[0].value(regs) {
+                0 => Flow::Next,
+                _ => Flow::Jump(ops[1].value(regs)),
+            });
+
+        let mut registers = Registers::default();
+        registers['a'] = 6;
+        registers['b'] = 7;
+
+        let result = Executor::new(&program, &instruction_set, registers)
+            .unwrap()
+            .run();
+        assert_eq!(result['c'], 42);
+        assert_eq!(program[0].to_string(), "cpy 0, c");
+    }
+
+    #[test]
+    fn validation() {
+        let instruction_set = cpu();
+
+        assert_eq!(
+            Executor::new(
+                &parse_program("noop\nmul 3"),
+                &instruction_set,
+                Registers::default()
+            )
+            .err(),
+            Some(VmError::UnknownInstruction {
+                opcode: "mul".to_string(),
+                line: 2
+            })
+        );
+        assert_eq!(
+            Executor::new(
+                &parse_program("addx 1, 2"),
+                &instruction_set,
+                Registers::default()
+            )
+            .err()
+            .unwrap()
+            .to_string(),
+            "Instruction 'addx' in line 1 requires 1 operands, got 2"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Result};
 use clap::Parser;
-use std::{fs, path::PathBuf};
+use helpers::rendering::RenderOptions;
+use std::{fmt::Write, fs, path::PathBuf};
 
 pub mod helpers;
 
@@ -14,13 +15,20 @@ solutions! {
     (day06, task1, task2)
     (day07, task1, task2)
     (day08, task1, task2)
+    (day09, task1, task2)
+    (day10, task1, task2)
 }
 
 // DAILY: Add new reference solutions here
-reworked_solutions! {}
+reworked_solutions! {
+    (day04, task1, task2)
+}
 
 // DAILY: Add new renderers here
-renderers! {}
+renderers! {
+    (day09, task1, task2)
+    (day10, task2)
+}
 
 // Command line arguments
 #[derive(Parser)]
@@ -45,6 +53,9 @@ pub struct Options {
     /// Render the task visually, if available
     #[arg(long)]
     pub render: bool,
+
+    #[command(flatten)]
+    pub render_options: RenderOptions,
 }
 
 fn main() -> Result<()> {
@@ -60,12 +71,19 @@ fn main() -> Result<()> {
     })?;
 
     if opts.render {
-        let artifacts = run_renderer(opts.day, opts.task, &data)?;
-        println!("─ Rendering artifacts: ─────────────────────────");
+        let artifacts = run_renderer(opts.day, opts.task, &data, &opts.render_options)?;
+        let mut summary = String::from("─ Rendering artifacts: ─────────────────────────\n");
         for artifact in artifacts {
-            println!("{}", artifact);
+            writeln!(summary, "{}", artifact)?;
+        }
+        summary.push_str("────────────────────────────────────────────────\n");
+
+        // Keep the summary out of renderings that are piped into other programs
+        if opts.render_options.writes_to_stdout() {
+            eprint!("{}", summary);
+        } else {
+            print!("{}", summary);
         }
-        println!("────────────────────────────────────────────────");
         return Ok(());
     }
 
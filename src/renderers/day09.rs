@@ -0,0 +1,117 @@
+use rgb::RGBA8;
+
+use crate::{
+    helpers::{
+        grid::{Direction, Point},
+        rendering::{
+            Artifact, Collector, Recording, RenderDefaults, RenderError, RenderOptions, ToColor,
+        },
+        sparse_grid::{Bounds, SparseGrid},
+    },
+    solutions::day09::{steps, Rope},
+};
+
+const DEFAULTS: RenderDefaults = RenderDefaults {
+    scale: 4,
+    frame_delay: 0.03,
+    hold_last: 1.0,
+};
+
+/// The size of the visible region, in cells.
+const VIEW_SIZE: usize = 61;
+/// How close the head may get to the edge of the visible region before the camera follows.
+/// Large enough to keep a ten knot rope in view.
+const CAMERA_MARGIN: usize = 12;
+
+#[derive(Clone, Copy)]
+enum Cell {
+    Empty,
+    Visited,
+    Knot,
+    Tail,
+    Head,
+}
+
+impl ToColor for Cell {
+    fn to_color(&self) -> RGBA8 {
+        match self {
+            Cell::Empty => RGBA8::new(15, 15, 35, 255),
+            Cell::Visited => RGBA8::new(70, 70, 110, 255),
+            Cell::Knot => RGBA8::new(220, 220, 220, 255),
+            Cell::Tail => RGBA8::new(255, 200, 0, 255),
+            Cell::Head => RGBA8::new(230, 40, 40, 255),
+        }
+    }
+}
+
+/// Simulates the rope, adding one frame after every step of the head.
+fn render(
+    motions: &[(Direction, u32)],
+    num_knots: usize,
+    frame_delay: f64,
+    collector: &mut impl Collector,
+) -> Result<(), RenderError> {
+    let mut rope = Rope::new(num_knots);
+    let mut visited = SparseGrid::new();
+    visited.insert(rope.tail(), ());
+
+    let mut camera = Bounds::centered_on(
+        Point::ORIGIN,
+        VIEW_SIZE - 2 * CAMERA_MARGIN,
+        VIEW_SIZE - 2 * CAMERA_MARGIN,
+    );
+
+    let mut add_frame = |rope: &Rope, visited: &SparseGrid<()>, frame: usize| {
+        camera = camera.follow(rope.head());
+        let viewport = camera.grow(CAMERA_MARGIN);
+
+        let mut image = visited.to_array(viewport, |cell| match cell {
+            Some(()) => Cell::Visited,
+            None => Cell::Empty,
+        });
+        for (i, &knot) in rope.knots().iter().enumerate().rev() {
+            if let Some(index) = (knot - viewport.min).to_index() {
+                if let Some(pixel) = image.get_mut(index) {
+                    *pixel = match i {
+                        0 => Cell::Head,
+                        i if i == num_knots - 1 => Cell::Tail,
+                        _ => Cell::Knot,
+                    };
+                }
+            }
+        }
+
+        collector.add_frame(&image, frame as f64 * frame_delay)
+    };
+
+    add_frame(&rope, &visited, 0)?;
+    for (frame, direction) in steps(motions).enumerate() {
+        rope.step(direction);
+        visited.insert(rope.tail(), ());
+        add_frame(&rope, &visited, frame + 1)?;
+    }
+
+    Ok(())
+}
+
+pub fn task1(
+    motions: &[(Direction, u32)],
+    options: &RenderOptions,
+) -> Result<Vec<Artifact>, RenderError> {
+    let settings = options.settings("day09_task1", DEFAULTS);
+    let artifact = Recording::record(&settings, |recording| {
+        render(motions, 2, settings.frame_delay, recording)
+    })?;
+    Ok(vec![artifact])
+}
+
+pub fn task2(
+    motions: &[(Direction, u32)],
+    options: &RenderOptions,
+) -> Result<Vec<Artifact>, RenderError> {
+    let settings = options.settings("day09_task2", DEFAULTS);
+    let artifact = Recording::record(&settings, |recording| {
+        render(motions, 10, settings.frame_delay, recording)
+    })?;
+    Ok(vec![artifact])
+}
@@ -0,0 +1,94 @@
+use ndarray::Array2;
+use rgb::RGBA8;
+
+use crate::{
+    helpers::{
+        rendering::{
+            Artifact, Collector, Recording, RenderDefaults, RenderError, RenderOptions, ToChar,
+            ToColor,
+        },
+        vm::Instruction,
+    },
+    solutions::day10::{beam_position, is_lit, x_values, CRT_HEIGHT, CRT_WIDTH},
+};
+
+/// Holds the finished screen for a while at the end of the animation.
+const DEFAULTS: RenderDefaults = RenderDefaults {
+    scale: 10,
+    frame_delay: 0.04,
+    hold_last: 3.0,
+};
+
+#[derive(Clone, Copy)]
+enum Pixel {
+    Unlit,
+    Lit,
+    Sprite,
+    Beam { lit: bool },
+}
+
+impl ToColor for Pixel {
+    fn to_color(&self) -> RGBA8 {
+        match self {
+            Pixel::Unlit => RGBA8::new(10, 20, 10, 255),
+            Pixel::Lit => RGBA8::new(60, 255, 90, 255),
+            Pixel::Sprite => RGBA8::new(30, 70, 35, 255),
+            Pixel::Beam { lit: true } => RGBA8::new(255, 255, 255, 255),
+            Pixel::Beam { lit: false } => RGBA8::new(200, 40, 40, 255),
+        }
+    }
+}
+
+impl ToChar for Pixel {
+    fn to_char(&self) -> char {
+        match self {
+            Pixel::Unlit => '.',
+            Pixel::Lit => '#',
+            Pixel::Sprite => '-',
+            Pixel::Beam { .. } => '@',
+        }
+    }
+}
+
+/// Adds one frame for every pixel the beam draws.
+fn render(
+    program: &[Instruction],
+    frame_delay: f64,
+    collector: &mut impl Collector,
+) -> Result<(), RenderError> {
+    let mut screen = Array2::from_elem((CRT_HEIGHT, CRT_WIDTH), Pixel::Unlit);
+
+    for (cycle, x) in x_values(program).into_iter().take(CRT_WIDTH * CRT_HEIGHT) {
+        let (row, col) = beam_position(cycle);
+        let lit = is_lit(cycle, x);
+
+        let mut frame = screen.clone();
+        for sprite_col in x - 1..=x + 1 {
+            if let Some(pixel @ Pixel::Unlit) = usize::try_from(sprite_col)
+                .ok()
+                .and_then(|sprite_col| frame.get_mut((row, sprite_col)))
+            {
+                *pixel = Pixel::Sprite;
+            }
+        }
+        frame[(row, col)] = Pixel::Beam { lit };
+        collector.add_text_frame(&frame, (cycle - 1) as f64 * frame_delay)?;
+
+        if lit {
+            screen[(row, col)] = Pixel::Lit;
+        }
+    }
+
+    collector.add_text_frame(&screen, (CRT_WIDTH * CRT_HEIGHT) as f64 * frame_delay)
+}
+
+pub fn task2(
+    program: &[Instruction],
+    options: &RenderOptions,
+) -> Result<Vec<Artifact>, RenderError> {
+    let settings = options.settings("day10_task2", DEFAULTS);
+    let artifact = Recording::record(&settings, |recording| {
+        render(program, settings.frame_delay, recording)
+    })?;
+    Ok(vec![artifact])
+}
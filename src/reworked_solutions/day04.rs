@@ -0,0 +1,47 @@
+use crate::helpers::intervals::IntervalSet;
+
+mod parser {
+    use std::ops::RangeInclusive;
+
+    pub use crate::helpers::nom::*;
+
+    fn range(input: &str) -> VResult<'_, RangeInclusive<u32>> {
+        map(separated_pair(u32, char('-'), u32), |(s, e)| s..=e)(input)
+    }
+
+    pub fn line(input: &str) -> VResult<'_, (RangeInclusive<u32>, RangeInclusive<u32>)> {
+        separated_pair(range, char(','), range)(input)
+    }
+}
+
+pub fn parse_input(input_data: &str) -> Vec<(IntervalSet<u32>, IntervalSet<u32>)> {
+    input_data
+        .trim_end()
+        .lines()
+        .map(parser::line)
+        .map(parser::finalize(input_data))
+        .map(|(a, b)| (a.into(), b.into()))
+        .collect()
+}
+
+pub fn task1(input: &[(IntervalSet<u32>, IntervalSet<u32>)]) -> usize {
+    input
+        .iter()
+        .filter(|(a, b)| a.contains_set(b) || b.contains_set(a))
+        .count()
+}
+
+pub fn task2(input: &[(IntervalSet<u32>, IntervalSet<u32>)]) -> usize {
+    input.iter().filter(|(a, b)| a.overlaps(b)).count()
+}
+
+crate::aoc_tests! {
+    task1: {
+        simple => 2,
+        complex => 459,
+    },
+    task2: {
+        simple => 4,
+        complex => 779,
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use crate::helpers::grid::{Direction, Point};
+
+mod parser {
+    use crate::helpers::grid::Direction;
+
+    pub use crate::helpers::nom::*;
+
+    fn direction(input: &str) -> VResult<'_, Direction> {
+        alt((
+            map(char('U'), |_| Direction::Up),
+            map(char('R'), |_| Direction::Right),
+            map(char('D'), |_| Direction::Down),
+            map(char('L'), |_| Direction::Left),
+        ))(input)
+    }
+
+    pub fn motions(input: &str) -> VResult<'_, Vec<(Direction, u32)>> {
+        separated_list1(line_ending, separated_pair(direction, space1, u32))(input)
+    }
+}
+
+pub fn parse_input(input_data: &str) -> Vec<(Direction, u32)> {
+    let input_data = input_data.trim_end();
+    parser::finalize(input_data)(parser::motions(input_data))
+}
+
+/// A rope made of knots, where every knot follows the one in front of it.
+#[derive(Debug, Clone)]
+pub struct Rope {
+    knots: Vec<Point>,
+}
+
+impl Rope {
+    pub fn new(num_knots: usize) -> Self {
+        assert!(num_knots > 0, "A rope needs at least one knot");
+        Self {
+            knots: vec![Point::ORIGIN; num_knots],
+        }
+    }
+
+    pub fn knots(&self) -> &[Point] {
+        &self.knots
+    }
+
+    pub fn head(&self) -> Point {
+        self.knots[0]
+    }
+
+    pub fn tail(&self) -> Point {
+        self.knots[self.knots.len() - 1]
+    }
+
+    /// Moves the head by a single step and drags the remaining knots along.
+    pub fn step(&mut self, direction: Direction) {
+        self.knots[0] = self.knots[0].step(direction);
+
+        for i in 1..self.knots.len() {
+            let leader = self.knots[i - 1];
+            let knot = &mut self.knots[i];
+            if leader.chebyshev_distance(*knot) <= 1 {
+                break;
+            }
+            let offset = leader - *knot;
+            *knot += Point::new(offset.row.signum(), offset.col.signum());
+        }
+    }
+}
+
+/// Splits the motions into single steps.
+pub fn steps(motions: &[(Direction, u32)]) -> impl Iterator<Item = Direction> + '_ {
+    motions
+        .iter()
+        .flat_map(|&(direction, distance)| std::iter::repeat_n(direction, distance as usize))
+}
+
+fn count_tail_positions(motions: &[(Direction, u32)], num_knots: usize) -> usize {
+    let mut rope = Rope::new(num_knots);
+    let mut visited = HashSet::from([rope.tail()]);
+
+    for direction in steps(motions) {
+        rope.step(direction);
+        visited.insert(rope.tail());
+    }
+
+    visited.len()
+}
+
+pub fn task1(motions: &[(Direction, u32)]) -> usize {
+    count_tail_positions(motions, 2)
+}
+
+pub fn task2(motions: &[(Direction, u32)]) -> usize {
+    count_tail_positions(motions, 10)
+}
+
+crate::aoc_tests! {
+    task1: {
+        simple => 13,
+        simple2 => 88,
+        complex => 6197,
+    },
+    task2: {
+        simple => 1,
+        simple2 => 36,
+        complex => 2562,
+    }
+}
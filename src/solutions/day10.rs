@@ -0,0 +1,106 @@
+use ndarray::Array2;
+
+use crate::helpers::{
+    ocr::read_letters,
+    vm::{parse_program, Executor, Flow, Instruction, InstructionSet, Registers},
+};
+
+pub const CRT_WIDTH: usize = 40;
+pub const CRT_HEIGHT: usize = 6;
+
+pub fn parse_input(input_data: &str) -> Vec<Instruction> {
+    parse_program(input_data)
+}
+
+fn instruction_set() -> InstructionSet {
+    InstructionSet::new()
+        .with("noop", 0, 1, |_, _| Flow::Next)
+        .with("addx", 1, 2, |registers, operands| {
+            registers['x'] += operands[0].value(registers);
+            Flow::Next
+        })
+}
+
+/// The value of the `X` register during every cycle, starting with cycle 1.
+pub fn x_values(program: &[Instruction]) -> Vec<(usize, i64)> {
+    let mut registers = Registers::default();
+    registers['x'] = 1;
+
+    let instruction_set = instruction_set();
+    Executor::new(program, &instruction_set, registers)
+        .unwrap()
+        .map(|(cycle, registers)| (cycle, registers['x']))
+        .collect()
+}
+
+/// Whether the pixel drawn during the given cycle is lit, with the sprite at position `x`.
+pub fn is_lit(cycle: usize, x: i64) -> bool {
+    let column = ((cycle - 1) % CRT_WIDTH) as i64;
+    (column - x).abs() <= 1
+}
+
+/// The position of the pixel that gets drawn during the given cycle.
+pub fn beam_position(cycle: usize) -> (usize, usize) {
+    ((cycle - 1) / CRT_WIDTH, (cycle - 1) % CRT_WIDTH)
+}
+
+pub fn task1(program: &[Instruction]) -> i64 {
+    x_values(program)
+        .into_iter()
+        // The signal is measured in the middle of every row
+        .filter(|(cycle, _)| cycle % CRT_WIDTH == CRT_WIDTH / 2)
+        .map(|(cycle, x)| cycle as i64 * x)
+        .sum()
+}
+
+/// The pixels the program draws on the CRT.
+fn draw_screen(program: &[Instruction]) -> Array2<bool> {
+    let mut screen = Array2::from_elem((CRT_HEIGHT, CRT_WIDTH), false);
+    for (cycle, x) in x_values(program).into_iter().take(CRT_WIDTH * CRT_HEIGHT) {
+        screen[beam_position(cycle)] = is_lit(cycle, x);
+    }
+    screen
+}
+
+pub fn task2(program: &[Instruction]) -> String {
+    read_letters(&draw_screen(program))
+}
+
+crate::aoc_tests! {
+    task1: {
+        simple => 13140,
+        complex => 12560,
+    },
+    task2: {
+        complex => "PLPAFBCL",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_example_screen() {
+        let data = include_str!("../../input_data/day10_simple.txt");
+
+        let screen = draw_screen(&parse_input(data));
+
+        let rows = screen
+            .rows()
+            .into_iter()
+            .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            rows,
+            [
+                "##..##..##..##..##..##..##..##..##..##..",
+                "###...###...###...###...###...###...###.",
+                "####....####....####....####....####....",
+                "#####.....#####.....#####.....#####.....",
+                "######......######......######......####",
+                "#######.......#######.......#######.....",
+            ]
+        );
+    }
+}